@@ -0,0 +1,144 @@
+//! Finds every challenge under `src/setN/challNN.rs`, so that none has to be registered by hand:
+//!
+//! - `sets.rs` declares each one as module `setN::challNN` (included by `lib.rs`);
+//! - `registry.rs` lists each one's `ChallNN` in `CHALLENGES` and its set in `SETS`, and generates
+//!   a test checking it against its known answer and its set, ignored if the challenge is slow
+//!   (included by `challenge.rs`).
+//!
+//! A challenge is slow if its module has the line `const SLOW: bool = true;`, and its
+//! `Challenge::slow` must then return `SLOW`. Any other way of overriding `slow` is rejected, as
+//! the build script couldn't tell what it returns.
+
+use std::{env, fmt::Write, fs, path::Path};
+
+struct Found {
+    set: u8,
+    number: u8,
+    path: String,
+    slow: bool,
+}
+
+fn number_after(prefix: &str, name: &str) -> Option<u8> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+/// Whether the challenge at `path` is slow, going by its `SLOW` marker.
+fn is_slow(path: &Path, source: &str) -> bool {
+    let mut marker = None;
+    for line in source.lines() {
+        let Some(value) = line
+            .strip_prefix("const SLOW: bool = ")
+            .and_then(|rest| rest.strip_suffix(';'))
+        else {
+            continue;
+        };
+        assert!(
+            marker.is_none(),
+            "{}: `const SLOW` declared more than once",
+            path.display()
+        );
+        marker =
+            Some(value.parse::<bool>().unwrap_or_else(|_| {
+                panic!("{}: `SLOW` must be `true` or `false`", path.display())
+            }));
+    }
+
+    if let Some(start) = source.find("fn slow(") {
+        let body = source[start..]
+            .split_once('{')
+            .and_then(|(_, rest)| rest.split_once('}'))
+            .map(|(body, _)| body.trim());
+        assert!(
+            marker.is_some() && body == Some("SLOW"),
+            "{}: `Challenge::slow` must return a top-level `const SLOW: bool`",
+            path.display()
+        );
+    }
+
+    marker.unwrap_or(false)
+}
+
+fn main() {
+    let src = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    println!("cargo:rerun-if-changed={}", src.display());
+
+    let mut found = vec![];
+    for set_dir in fs::read_dir(&src).unwrap() {
+        let set_dir = set_dir.unwrap().path();
+        let Some(set) = set_dir
+            .file_name()
+            .and_then(|name| number_after("set", name.to_str()?))
+        else {
+            continue;
+        };
+
+        for file in fs::read_dir(&set_dir).unwrap() {
+            let file = file.unwrap().path();
+            let Some(number) = file
+                .file_stem()
+                .and_then(|stem| number_after("chall", stem.to_str()?))
+                .filter(|_| file.extension().is_some_and(|ext| ext == "rs"))
+            else {
+                continue;
+            };
+
+            let source = fs::read_to_string(&file).unwrap();
+            found.push(Found {
+                set,
+                number,
+                path: file.display().to_string(),
+                slow: is_slow(&file, &source),
+            });
+        }
+    }
+    found.sort_by_key(|challenge| challenge.number);
+
+    let mut sets = String::new();
+    let mut registry = String::from("static CHALLENGES: &[&dyn Challenge] = &[\n");
+    let mut sets_by_number = String::from("static SETS: &[(u8, u8)] = &[\n");
+    let mut tests = String::from("#[cfg(test)]\nmod self_check {\n");
+    let mut current_set = None;
+    for Found {
+        set,
+        number,
+        path,
+        slow,
+    } in &found
+    {
+        if current_set != Some(set) {
+            if current_set.is_some() {
+                sets.push_str("}\n");
+            }
+            writeln!(sets, "pub mod set{set} {{").unwrap();
+            current_set = Some(set);
+        }
+        writeln!(
+            sets,
+            "    #[path = {path:?}]\n    pub mod chall{number:02};"
+        )
+        .unwrap();
+
+        let challenge = format!("crate::set{set}::chall{number:02}::Chall{number:02}");
+        writeln!(registry, "    &{challenge},").unwrap();
+        writeln!(sets_by_number, "    ({number}, {set}),").unwrap();
+
+        if *slow {
+            tests.push_str("    #[ignore = \"slow\"]\n");
+        }
+        writeln!(
+            tests,
+            "    #[test]\n    fn chall{number:02}() {{\n        super::self_check(&{challenge}, {number}, {set});\n    }}"
+        )
+        .unwrap();
+    }
+    if current_set.is_some() {
+        sets.push_str("}\n");
+    }
+    registry.push_str("];\n\n");
+    sets_by_number.push_str("];\n\n");
+    tests.push_str("}\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+    fs::write(out.join("sets.rs"), sets).unwrap();
+    fs::write(out.join("registry.rs"), registry + &sets_by_number + &tests).unwrap();
+}
@@ -0,0 +1,390 @@
+use std::{collections::HashMap, iter};
+
+use itertools::Itertools;
+
+use crate::{
+    error::{self, CryptopalsError},
+    xor,
+};
+
+/// Finds the single-byte XOR key that makes a ciphertext look the most like English.
+/// Returns the score, the key and the plaintext.
+pub fn find_single_byte_key(c: &[u8]) -> Option<(usize, u8, Vec<u8>)> {
+    let mut top_candidate = None;
+
+    for k in u8::MIN..=u8::MAX {
+        let candidate = xor::single_byte_xor(c, k);
+        let score = score_english_phrase(&candidate);
+
+        match top_candidate {
+            Some((top_score, _, _)) if top_score >= score => {} // ignore
+            _ => top_candidate = Some((score, k, candidate)),
+        }
+    }
+
+    top_candidate
+}
+
+fn score_english_phrase(phrase: &[u8]) -> usize {
+    phrase
+        .iter()
+        .filter(|x| x.is_ascii_alphabetic() || **x == b' ')
+        .count()
+}
+
+/// Finds which of the sequences was encrypted with single-byte XOR. Returns the score, the key and
+/// the plaintext.
+pub fn find_single_byte_encryption(seqs: &[Vec<u8>]) -> Option<(usize, u8, Vec<u8>)> {
+    let mut top_candidate = None;
+
+    for seq in seqs {
+        let result = find_single_byte_key(seq);
+        if let Some((score, _, _)) = result {
+            match top_candidate {
+                Some((top_score, _, _)) if top_score > score => {} // ignore
+                _ => top_candidate = result,
+            }
+        }
+    }
+
+    top_candidate
+}
+
+/// Breaks repeating-key XOR. Returns the key and plaintext.
+pub fn decrypt_repeating_key_xor(ciphertext: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let key_size: usize = find_key_size(ciphertext)?.into();
+
+    let transposed = (0..key_size).map(|i| {
+        ciphertext
+            .iter()
+            .skip(i)
+            .step_by(key_size)
+            .copied()
+            .collect_vec()
+    });
+
+    let key: Vec<u8> = transposed
+        .map(|t_block| find_single_byte_key(&t_block).map(|(_, k, _)| k))
+        .collect::<Option<_>>()?;
+
+    let msg = xor::repeating_key_xor(ciphertext, &key);
+
+    Some((key, msg))
+}
+
+/// Guesses the key size of repeating-key XOR, as the one with the smallest normalized Hamming
+/// distance between consecutive blocks.
+pub fn find_key_size(ciphertext: &[u8]) -> Option<u8> {
+    let mut top_candidate = None;
+    let mut top_score = f64::MAX;
+
+    for candidate_size in 2..=40_u8 {
+        let chunks = ciphertext.iter().chunks(candidate_size.into());
+        let it = chunks
+            .into_iter()
+            .map(|block| block.copied().collect_vec())
+            .tuple_windows();
+
+        let mut distances_normalized = vec![];
+
+        for (a, b) in it {
+            // ignore last chunk if it's smaller (total length not divisible by candidate_size)
+            let Ok(distance) = xor::hamming_distance(&a, &b) else {
+                break;
+            };
+
+            distances_normalized.push(f64::from(distance) / f64::from(candidate_size));
+        }
+
+        if !distances_normalized.is_empty() {
+            let sum: f64 = distances_normalized.iter().sum();
+            let avg = sum / (distances_normalized.len() as f64);
+            if avg < top_score {
+                top_candidate = Some(candidate_size);
+                top_score = avg;
+            }
+        }
+    }
+
+    top_candidate
+}
+
+/// Counts the 16-byte blocks that repeat an earlier block of the ciphertext, which ECB gives away
+/// for repeated plaintext blocks.
+pub fn count_repeated_blocks(ciphertext: &[u8]) -> usize {
+    let blocks = ciphertext.chunks_exact(16);
+    blocks.len() - blocks.unique().count()
+}
+
+/// Finds which of the ciphertexts is the most likely to have been encrypted with ECB: the one with
+/// the most repeated blocks, or the first of them if that's a tie. Only `None` without any
+/// ciphertext at all.
+pub fn detect_aes_ecb(candidates: &[Vec<u8>]) -> Option<&Vec<u8>> {
+    // "the same 16 byte plaintext block will always produce the same 16 byte ciphertext"
+
+    let mut top_candidate = None;
+    let mut top_score = 0;
+
+    for candidate in candidates {
+        let score = count_repeated_blocks(candidate);
+
+        if top_candidate.is_none() || score > top_score {
+            top_candidate = Some(candidate);
+            top_score = score;
+        }
+    }
+
+    top_candidate
+}
+
+/// Tells whether an encryption oracle uses ECB.
+pub fn aes_ecb_detector<F>(f: &F) -> error::Result<bool>
+where
+    F: Fn(&[u8]) -> error::Result<Vec<u8>>,
+{
+    let plaintext = [0; 64];
+    let ciphertext = f(&plaintext)?;
+
+    // 2 equal consecutive blocks
+    Ok(ciphertext.chunks(16).tuple_windows().any(|(x, y)| x == y))
+}
+
+/// Finds the block size of an encryption oracle, along with the length of what it adds to the
+/// plaintext, which it must PKCS#7-pad.
+pub fn find_block_size<F>(f: &F) -> error::Result<(usize, usize)>
+where
+    F: Fn(&[u8]) -> error::Result<Vec<u8>>,
+{
+    let mut i = 1;
+    let mut output_size = None;
+    let block_size = loop {
+        let plaintext = iter::repeat_n(0x72, i).collect_vec();
+        let output = f(&plaintext)?;
+
+        match output_size {
+            None => output_size = Some(output.len()),
+            Some(size) if size < output.len() => break output.len() - size,
+            Some(_) => {}
+        }
+        i += 1;
+    };
+
+    // the output grew as soon as the padding needed a whole block, i.e., with what was added
+    // coming up to a whole number of blocks
+    let secret_len = output_size.unwrap() - i;
+
+    Ok((block_size, secret_len))
+}
+
+/// Byte-at-a-time ECB decryption: recovers the secret an ECB oracle appends to the plaintext.
+pub fn discover_unknown_suffix<F>(f: F) -> error::Result<Vec<u8>>
+where
+    F: Fn(&[u8]) -> error::Result<Vec<u8>>,
+{
+    let (block_size, secret_len) = find_block_size(&f)?;
+    if !aes_ecb_detector(&f)? {
+        return Err(CryptopalsError::Unsupported("Only ECB is supported"));
+    };
+
+    let mut secret = vec![];
+
+    while secret.len() < secret_len {
+        let output_base = iter::repeat_n(
+            0x72,
+            block_size
+                .saturating_sub(secret.len() % block_size)
+                .saturating_sub(1),
+        )
+        .collect_vec();
+        let output = f(&output_base)?;
+
+        let mut base = iter::repeat_n(
+            0x72,
+            block_size.saturating_sub(secret.len()).saturating_sub(1),
+        )
+        .collect_vec();
+        base.extend(secret[secret.len().saturating_sub(block_size - 1)..].iter());
+
+        let block_index = secret.len() / block_size;
+
+        for i in u8::MIN..=u8::MAX {
+            let mut candidate = base.clone();
+            candidate.push(i);
+            let result = f(&candidate)?;
+
+            let output_block = &output[block_index * block_size..][..block_size];
+            let result_block = &result[..block_size];
+            if output_block == result_block {
+                secret.push(i);
+                break;
+            }
+        }
+    }
+
+    Ok(secret)
+}
+
+/// Byte-at-a-time ECB decryption, for an oracle that also prepends an unknown (but fixed) prefix.
+pub fn discover_unknown_suffix_after_prefix<F>(f: F) -> error::Result<Vec<u8>>
+where
+    F: Fn(&[u8]) -> error::Result<Vec<u8>>,
+{
+    let (block_size, prefix_plus_secret_len) = find_block_size(&f)?;
+    if !aes_ecb_detector(&f)? {
+        return Err(CryptopalsError::Unsupported("Only ECB is supported"));
+    };
+
+    let prefix_length = find_unknown_prefix_length(&f, block_size)?;
+    let prefix_blocks = prefix_length / block_size + 1;
+    let payload_offset = block_size - (prefix_length % block_size);
+
+    let mut secret = vec![];
+
+    while secret.len() + prefix_length < prefix_plus_secret_len {
+        let output_base = iter::repeat_n(
+            0x72,
+            (block_size + payload_offset)
+                .saturating_sub(secret.len() % block_size)
+                .saturating_sub(1),
+        )
+        .collect_vec();
+        let output = f(&output_base)?;
+
+        let mut base = iter::repeat_n(
+            0x72,
+            (block_size + payload_offset)
+                .saturating_sub(secret.len())
+                .saturating_sub(1),
+        )
+        .collect_vec();
+        base.extend(
+            secret[secret
+                .len()
+                .saturating_sub(block_size - 1)
+                .saturating_sub(payload_offset)..]
+                .iter(),
+        );
+
+        let block_index = secret.len() / block_size;
+
+        for i in u8::MIN..=u8::MAX {
+            let mut candidate = base.clone();
+            candidate.push(i);
+            let result = f(&candidate)?;
+
+            let output_block = &output[(block_index + prefix_blocks) * block_size..][..block_size];
+            let result_block = &result[prefix_blocks * block_size..][..block_size];
+            if output_block == result_block {
+                secret.push(i);
+                break;
+            }
+        }
+    }
+
+    Ok(secret)
+}
+
+/// Finds the length of the prefix an ECB oracle prepends to the plaintext.
+pub fn find_unknown_prefix_length<F>(f: &F, block_size: usize) -> error::Result<usize>
+where
+    F: Fn(&[u8]) -> error::Result<Vec<u8>>,
+{
+    let mut i = block_size * 2;
+
+    loop {
+        let plaintext = iter::repeat_n(0x72, i).collect_vec();
+        let ciphertext = f(&plaintext)?;
+        if let Some(pos) = ciphertext
+            .chunks(block_size)
+            .tuple_windows()
+            .position(|(x, y)| x == y)
+        {
+            // 2 consecutive equal blocks
+            break Ok(pos * block_size - (i % block_size));
+        }
+        i += 1;
+    }
+}
+
+/// ECB cut-and-paste: makes an oracle like [`crate::oracles::ProfileOracle`] decrypt to an admin
+/// profile.
+pub fn make_admin_profile<E, D>(
+    encrypter: E,
+    decrypter: D,
+) -> error::Result<HashMap<String, String>>
+where
+    E: Fn(&[u8]) -> error::Result<Vec<u8>>,
+    D: Fn(&[u8]) -> error::Result<HashMap<String, String>>,
+{
+    // ..........."d=10&role=" || "admin"...padding
+    let payload = "xxxxxxxxxxadmin\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0bxxx";
+
+    let mut ciphertext = encrypter(payload.as_bytes())?;
+    ciphertext.copy_within(16..(2 * 16), 3 * 16);
+    ciphertext.copy_within((2 * 16).., 16);
+    ciphertext.truncate(ciphertext.len().saturating_sub(16));
+
+    decrypter(&ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{Format, Input};
+
+    use super::*;
+
+    #[test]
+    fn find_key_size_works() {
+        let ciphertext = Input::file("data/6.txt").decoded(Format::Base64).unwrap();
+        assert_eq!(Some(29), find_key_size(&ciphertext));
+    }
+
+    #[test]
+    fn find_key_size_works_on_very_different_blocks() {
+        // blocks of 40 bytes differ in up to 320 bits
+        let ciphertext = [[0; 32], [0xff; 32]].concat().repeat(4);
+
+        assert_eq!(Some(2), find_key_size(&ciphertext));
+    }
+
+    #[test]
+    fn find_single_byte_key_works_on_long_input() {
+        let plaintext = "a long line of plain English text ".repeat(20);
+        let ciphertext = xor::single_byte_xor(plaintext.as_bytes(), 0x42);
+        let (_, key, msg) = find_single_byte_key(&ciphertext).unwrap();
+        assert_eq!((0x42, plaintext.as_bytes()), (key, msg.as_slice()));
+    }
+
+    #[test]
+    fn discover_unknown_suffix_rejects_cbc() {
+        let key = crate::block::random_aes_key();
+        let oracle = |plaintext: &[u8]| {
+            let plaintext = crate::padding::pkcs7_pad(&[plaintext, b"secret"].concat());
+            crate::block::encrypt_aes_cbc(&plaintext, &key, None)
+        };
+        assert!(matches!(
+            discover_unknown_suffix(oracle),
+            Err(CryptopalsError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn detect_aes_ecb_looks_for_repeated_blocks() {
+        // long and full of repeated bytes, but not of repeated blocks
+        let noise = (0..400).map(|i| (i % 251) as u8).collect_vec();
+        let ecb = [0x2a; 16].repeat(4);
+        let candidates = [noise, ecb.clone()];
+        assert_eq!(Some(&ecb), detect_aes_ecb(&candidates));
+
+        let single = [(0..64).collect_vec()];
+        assert_eq!(Some(&single[0]), detect_aes_ecb(&single));
+        assert_eq!(None, detect_aes_ecb(&[]));
+    }
+
+    #[test]
+    fn count_repeated_blocks_works() {
+        let ciphertext = [[1; 16], [2; 16], [1; 16], [1; 16]].concat();
+        assert_eq!(2, count_repeated_blocks(&ciphertext));
+        assert_eq!(0, count_repeated_blocks(&ciphertext[..40]));
+    }
+}
@@ -0,0 +1,507 @@
+use std::io::{self, Read, Write};
+
+use crate::error::{self, CryptopalsError};
+
+/// Which two characters encode 62 and 63, after `A-Z`, `a-z` and `0-9`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// `+` and `/`.
+    Standard,
+    /// `-` and `_`, safe in URLs and file names (RFC 4648 §5).
+    UrlSafe,
+}
+
+impl Alphabet {
+    fn symbol(self, sextet: u8) -> u8 {
+        match (sextet, self) {
+            (0..=25, _) => b'A' + sextet,
+            (26..=51, _) => b'a' + (sextet - 26),
+            (52..=61, _) => b'0' + (sextet - 52),
+            (62, Self::Standard) => b'+',
+            (63, Self::Standard) => b'/',
+            (62, Self::UrlSafe) => b'-',
+            (63, Self::UrlSafe) => b'_',
+            _ => unreachable!("Not a sextet: {sextet}"),
+        }
+    }
+
+    fn sextet(self, symbol: u8) -> Option<u8> {
+        match (symbol, self) {
+            (b'A'..=b'Z', _) => Some(symbol - b'A'),
+            (b'a'..=b'z', _) => Some(symbol - b'a' + 26),
+            (b'0'..=b'9', _) => Some(symbol - b'0' + 52),
+            (b'+', Self::Standard) | (b'-', Self::UrlSafe) => Some(62),
+            (b'/', Self::Standard) | (b'_', Self::UrlSafe) => Some(63),
+            _ => None,
+        }
+    }
+}
+
+/// How to encode and decode: the alphabet, and whether groups are padded with `=`.
+///
+/// Decoding is strict by default, failing on anything outside the alphabet (line breaks
+/// included); [`Config::decode_lenient`] skips such characters instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub alphabet: Alphabet,
+    /// Whether encoding pads the last group, and decoding requires it to be padded. Without
+    /// padding, decoding takes input with or without it.
+    pub padding: bool,
+}
+
+pub const STANDARD: Config = Config {
+    alphabet: Alphabet::Standard,
+    padding: true,
+};
+
+pub const STANDARD_NO_PAD: Config = Config {
+    alphabet: Alphabet::Standard,
+    padding: false,
+};
+
+pub const URL_SAFE: Config = Config {
+    alphabet: Alphabet::UrlSafe,
+    padding: true,
+};
+
+pub const URL_SAFE_NO_PAD: Config = Config {
+    alphabet: Alphabet::UrlSafe,
+    padding: false,
+};
+
+impl Config {
+    /// Encodes bytes, padding the last group if this config pads.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let mut out = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            self.encode_chunk(chunk, &mut out);
+        }
+
+        String::from_utf8(out).expect("Base64 is ASCII")
+    }
+
+    /// Encodes up to 3 bytes into a group of up to 4 characters.
+    fn encode_chunk(&self, chunk: &[u8], out: &mut Vec<u8>) {
+        let group = chunk.iter().enumerate().fold(0_u32, |acc, (i, &byte)| {
+            acc | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            let sextet = (group >> (18 - 6 * i)) as u8 & 0b111111;
+            out.push(self.alphabet.symbol(sextet));
+        }
+        if self.padding {
+            out.extend(std::iter::repeat_n(b'=', 3 - chunk.len()));
+        }
+    }
+
+    /// Decodes base64, failing with the (byte) position of the first character outside the
+    /// alphabet, misplaced padding, or unused bits that aren't zero, or the length of the input if
+    /// the last group is incomplete.
+    pub fn decode(&self, input: &str) -> error::Result<Vec<u8>> {
+        let mut decoder = Decoder::new(*self, true);
+        let mut out = Vec::with_capacity(input.len() / 4 * 3);
+        for (position, &symbol) in input.as_bytes().iter().enumerate() {
+            decoder.push(position, symbol, &mut out)?;
+        }
+        decoder.finish(input.len(), &mut out)?;
+
+        Ok(out)
+    }
+
+    /// Decodes base64, skipping anything outside the alphabet (such as MIME line breaks), and
+    /// not checking unused bits.
+    ///
+    /// Still fails with the position of misplaced padding, or the length of the input if the last
+    /// group is incomplete.
+    pub fn decode_lenient(&self, input: &str) -> error::Result<Vec<u8>> {
+        let mut decoder = Decoder::new(*self, false);
+        let mut out = Vec::with_capacity(input.len() / 4 * 3);
+        for (position, &symbol) in input.as_bytes().iter().enumerate() {
+            if decoder.accepts(symbol) {
+                decoder.push(position, symbol, &mut out)?;
+            }
+        }
+        decoder.finish(input.len(), &mut out)?;
+
+        Ok(out)
+    }
+}
+
+/// Decodes symbols one at a time, given with their position in the input, for errors to point
+/// at.
+struct Decoder {
+    config: Config,
+    strict: bool,
+    group: u32,
+    /// Symbols in the current group, padding included.
+    len: usize,
+    padding: usize,
+    /// Where the last symbol that wasn't padding was.
+    last_position: usize,
+    /// Whether a padded group ended the input, so that nothing can follow.
+    ended: bool,
+}
+
+impl Decoder {
+    fn new(config: Config, strict: bool) -> Self {
+        Self {
+            config,
+            strict,
+            group: 0,
+            len: 0,
+            padding: 0,
+            last_position: 0,
+            ended: false,
+        }
+    }
+
+    /// Whether a lenient decoder should take this symbol rather than skip it.
+    fn accepts(&self, symbol: u8) -> bool {
+        symbol == b'=' || self.config.alphabet.sextet(symbol).is_some()
+    }
+
+    fn push(&mut self, position: usize, symbol: u8, out: &mut Vec<u8>) -> error::Result<()> {
+        let invalid = CryptopalsError::InvalidBase64 { position };
+        if self.ended {
+            return Err(invalid);
+        }
+
+        if symbol == b'=' {
+            self.padding += 1;
+            if self.padding > 2 {
+                return Err(invalid);
+            }
+        } else {
+            let sextet = self.config.alphabet.sextet(symbol).ok_or(invalid)?;
+            if self.padding > 0 {
+                // no non-padding allowed after padding
+                return Err(CryptopalsError::InvalidBase64 { position });
+            }
+            self.group |= u32::from(sextet) << (18 - 6 * self.len);
+            self.last_position = position;
+        }
+
+        self.len += 1;
+        if self.len == 4 {
+            self.ended = self.padding > 0;
+            self.flush(3 - self.padding, out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Outputs the `count` bytes the current group holds, and starts the next one.
+    fn flush(&mut self, count: usize, out: &mut Vec<u8>) -> error::Result<()> {
+        let unused = self.group & (0xffffff >> (8 * count));
+        if self.strict && unused != 0 {
+            return Err(CryptopalsError::InvalidBase64 {
+                position: self.last_position,
+            });
+        }
+
+        out.extend((0..count).map(|i| (self.group >> (16 - 8 * i)) as u8));
+        self.group = 0;
+        self.len = 0;
+        self.padding = 0;
+
+        Ok(())
+    }
+
+    /// Outputs what's left at the end of the input, which is at `end`.
+    fn finish(&mut self, end: usize, out: &mut Vec<u8>) -> error::Result<()> {
+        match self.len {
+            0 => Ok(()),
+            2 | 3 if !self.config.padding && self.padding == 0 => self.flush(self.len - 1, out),
+            _ => Err(CryptopalsError::InvalidBase64 { position: end }),
+        }
+    }
+}
+
+fn invalid_data(e: CryptopalsError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Encodes everything written to it into `inner`.
+///
+/// Call [`EncoderWriter::finish`] to write the last group: dropping it does too, but loses any
+/// error.
+pub struct EncoderWriter<W: Write> {
+    config: Config,
+    inner: Option<W>,
+    /// Bytes not encoded yet, until they make a group.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> EncoderWriter<W> {
+    pub fn new(inner: W, config: Config) -> Self {
+        Self {
+            config,
+            inner: Some(inner),
+            pending: Vec::with_capacity(3),
+        }
+    }
+
+    /// Writes the last group, and gives back the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_pending()?;
+        Ok(self.inner.take().expect("Only finished once"))
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let Some(inner) = &mut self.inner else {
+            return Ok(());
+        };
+
+        let mut out = vec![];
+        if !self.pending.is_empty() {
+            self.config.encode_chunk(&self.pending, &mut out);
+            self.pending.clear();
+        }
+        inner.write_all(&out)?;
+        inner.flush()
+    }
+}
+
+impl<W: Write> Write for EncoderWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = self.inner.as_mut().expect("Not written to after finishing");
+
+        let mut out = Vec::with_capacity((self.pending.len() + buf.len()) / 3 * 4);
+        let mut chunk = Vec::with_capacity(3);
+        for &byte in self.pending.iter().chain(buf) {
+            chunk.push(byte);
+            if chunk.len() == 3 {
+                self.config.encode_chunk(&chunk, &mut out);
+                chunk.clear();
+            }
+        }
+        inner.write_all(&out)?;
+        self.pending = chunk;
+
+        Ok(buf.len())
+    }
+
+    /// Flushes what was encoded so far, which leaves out up to 2 bytes that don't make a group
+    /// yet.
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for EncoderWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.write_pending();
+    }
+}
+
+/// Decodes everything read from `inner`, strictly unless made [`DecoderReader::lenient`].
+///
+/// Invalid base64 is an [`io::ErrorKind::InvalidData`] error wrapping the
+/// [`CryptopalsError::InvalidBase64`], with its position in the whole stream.
+pub struct DecoderReader<R: Read> {
+    inner: R,
+    decoder: Decoder,
+    /// How many bytes were read from `inner` so far.
+    position: usize,
+    /// Decoded bytes not read yet, from `start`.
+    decoded: Vec<u8>,
+    start: usize,
+    eof: bool,
+}
+
+impl<R: Read> DecoderReader<R> {
+    pub fn new(inner: R, config: Config) -> Self {
+        Self {
+            inner,
+            decoder: Decoder::new(config, true),
+            position: 0,
+            decoded: vec![],
+            start: 0,
+            eof: false,
+        }
+    }
+
+    /// Skips anything outside the alphabet, like [`Config::decode_lenient`].
+    pub fn lenient(mut self) -> Self {
+        self.decoder.strict = false;
+        self
+    }
+
+    /// Decodes more of `inner`, until there's something to read or it ends.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buf = [0; 4096];
+        while self.start == self.decoded.len() && !self.eof {
+            self.decoded.clear();
+            self.start = 0;
+
+            let n = match self.inner.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if n == 0 {
+                self.eof = true;
+                self.decoder
+                    .finish(self.position, &mut self.decoded)
+                    .map_err(invalid_data)?;
+            }
+
+            for &symbol in &buf[..n] {
+                if self.decoder.strict || self.decoder.accepts(symbol) {
+                    self.decoder
+                        .push(self.position, symbol, &mut self.decoded)
+                        .map_err(invalid_data)?;
+                }
+                self.position += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill()?;
+
+        let available = &self.decoded[self.start..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.start += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4648 §10.
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn rfc4648_vectors() {
+        for (plain, encoded) in VECTORS {
+            assert_eq!(encoded, STANDARD.encode(plain.as_bytes()));
+            assert_eq!(plain.as_bytes(), STANDARD.decode(encoded).unwrap());
+
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(unpadded, STANDARD_NO_PAD.encode(plain.as_bytes()));
+            assert_eq!(plain.as_bytes(), STANDARD_NO_PAD.decode(unpadded).unwrap());
+            assert_eq!(plain.as_bytes(), STANDARD_NO_PAD.decode(encoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn url_safe_works() {
+        let bytes = [0xfb, 0xef, 0xff, 0xfe];
+        assert_eq!("++///g==", STANDARD.encode(&bytes));
+        assert_eq!("--___g==", URL_SAFE.encode(&bytes));
+        assert_eq!("--___g", URL_SAFE_NO_PAD.encode(&bytes));
+        assert_eq!(bytes.to_vec(), URL_SAFE_NO_PAD.decode("--___g").unwrap());
+        assert!(matches!(
+            URL_SAFE.decode("++///g=="),
+            Err(CryptopalsError::InvalidBase64 { position: 0 })
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_garbage() {
+        let invalid_at = |config: Config, input: &str| match config.decode(input) {
+            Err(CryptopalsError::InvalidBase64 { position }) => position,
+            result => panic!("{input:?} decoded to {result:?}"),
+        };
+        assert_eq!(4, invalid_at(STANDARD, "Zm9v\nYmFy"));
+        assert_eq!(3, invalid_at(STANDARD, "Zm9$"));
+        assert_eq!(3, invalid_at(STANDARD, "Zm9"));
+        assert_eq!(3, invalid_at(STANDARD_NO_PAD, "Zm=v"));
+        assert_eq!(4, invalid_at(STANDARD, "Zg==Zg=="));
+        assert_eq!(1, invalid_at(STANDARD, "Zh=="));
+        assert_eq!(5, invalid_at(STANDARD_NO_PAD, "Zm9vY"));
+        assert_eq!(7, invalid_at(STANDARD_NO_PAD, "Zm9vYg="));
+    }
+
+    #[test]
+    fn decode_lenient_skips_garbage() {
+        assert_eq!(
+            b"foobar".to_vec(),
+            STANDARD.decode_lenient("Zm9v\r\nYm Fy\n").unwrap()
+        );
+        assert_eq!(b"f".to_vec(), STANDARD.decode_lenient("Zh==").unwrap());
+        assert!(matches!(
+            STANDARD.decode_lenient("Zm9\n"),
+            Err(CryptopalsError::InvalidBase64 { position: 4 })
+        ));
+    }
+
+    /// Reads at most `n` bytes at a time, to split groups across reads.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(self.1).min(buf.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn encoder_writer_works() {
+        let plain: Vec<u8> = (0..=255).collect();
+        for config in [STANDARD, URL_SAFE_NO_PAD] {
+            let mut writer = EncoderWriter::new(vec![], config);
+            for chunk in plain.chunks(7) {
+                writer.write_all(chunk).unwrap();
+            }
+            let encoded = writer.finish().unwrap();
+            assert_eq!(config.encode(&plain).as_bytes(), encoded);
+        }
+
+        let mut encoded = vec![];
+        EncoderWriter::new(&mut encoded, STANDARD)
+            .write_all(b"fooba")
+            .unwrap();
+        assert_eq!(b"Zm9vYmE=".to_vec(), encoded);
+    }
+
+    #[test]
+    fn decoder_reader_works() {
+        let plain: Vec<u8> = (0..=255).collect();
+        let encoded = STANDARD.encode(&plain);
+        let mut decoded = vec![];
+        DecoderReader::new(Trickle(encoded.as_bytes(), 5), STANDARD)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(plain, decoded);
+
+        let wrapped = "Zm9v\nYmFy\n";
+        let mut decoded = vec![];
+        DecoderReader::new(Trickle(wrapped.as_bytes(), 3), STANDARD)
+            .lenient()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(b"foobar".to_vec(), decoded);
+
+        let error = DecoderReader::new(Trickle(wrapped.as_bytes(), 3), STANDARD)
+            .read_to_end(&mut vec![])
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert!(matches!(
+            error.into_inner().unwrap().downcast_ref(),
+            Some(CryptopalsError::InvalidBase64 { position: 4 })
+        ));
+    }
+}
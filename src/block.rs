@@ -0,0 +1,192 @@
+use itertools::Itertools;
+use openssl::symm;
+
+use crate::{
+    error::{self, CryptopalsError},
+    padding, xor,
+};
+
+pub fn random_aes_key() -> Vec<u8> {
+    let bytes: [u8; 16] = rand::random();
+
+    bytes.to_vec()
+}
+
+/// Checks that the text is made of whole 16-byte blocks, which is all these modes take: padding
+/// is up to the caller.
+fn check_whole_blocks(text: &[u8]) -> error::Result<()> {
+    if text.len().is_multiple_of(16) {
+        Ok(())
+    } else {
+        Err(CryptopalsError::LengthMismatch {
+            expected: text.len().next_multiple_of(16),
+            actual: text.len(),
+        })
+    }
+}
+
+/// Encrypts with AES-128 in ECB mode. The plaintext has to be whole blocks.
+pub fn encrypt_aes_ecb(plaintext: &[u8], key: &[u8]) -> error::Result<Vec<u8>> {
+    check_whole_blocks(plaintext)?;
+    let cipher = symm::Cipher::aes_128_ecb();
+    let mut encrypter = symm::Crypter::new(cipher, symm::Mode::Encrypt, key, None)?;
+    encrypter.pad(false);
+
+    // openssl wants room for an extra block
+    let mut ciphertext = vec![0; plaintext.len() + cipher.block_size()];
+    encrypter.update(plaintext, &mut ciphertext)?;
+
+    ciphertext.truncate(plaintext.len());
+    Ok(ciphertext)
+}
+
+/// Decrypts with AES-128 in ECB mode. The ciphertext has to be whole blocks.
+pub fn decrypt_aes_ecb(ciphertext: &[u8], key: &[u8]) -> error::Result<Vec<u8>> {
+    check_whole_blocks(ciphertext)?;
+    let cipher = symm::Cipher::aes_128_ecb();
+    let mut decrypter = symm::Crypter::new(cipher, symm::Mode::Decrypt, key, None)?;
+    decrypter.pad(false);
+
+    // openssl wants room for an extra block
+    let mut plaintext = vec![0; ciphertext.len() + cipher.block_size()];
+    decrypter.update(ciphertext, &mut plaintext)?;
+
+    plaintext.truncate(ciphertext.len());
+    Ok(plaintext)
+}
+
+/// AES-128 in CBC mode, one block at a time.
+pub struct StatefulCBC {
+    key: Vec<u8>,
+    last_block: Vec<u8>,
+}
+
+impl StatefulCBC {
+    /// If no IV is given, a fixed all-zero IV is used.
+    pub fn new(key: &[u8], iv: Option<Vec<u8>>) -> Self {
+        Self {
+            key: key.to_vec(),
+            last_block: iv.unwrap_or_else(|| vec![0; 16]),
+        }
+    }
+
+    pub fn encrypt_block(&mut self, block: &[u8]) -> error::Result<Vec<u8>> {
+        let xord = xor::xor(block, &self.last_block)?;
+        let result = encrypt_aes_ecb(&xord, &self.key)?;
+        self.last_block = result.clone();
+
+        Ok(result)
+    }
+
+    pub fn decrypt_block(&mut self, block: &[u8]) -> error::Result<Vec<u8>> {
+        let result = decrypt_aes_ecb(block, &self.key)?;
+        let xord = xor::xor(&result, &self.last_block)?;
+        self.last_block = block.to_vec();
+
+        Ok(xord)
+    }
+}
+
+/// Encrypts with AES-128 in CBC mode. The plaintext has to be whole blocks.
+pub fn encrypt_aes_cbc(
+    plaintext: &[u8],
+    key: &[u8],
+    iv: Option<Vec<u8>>,
+) -> error::Result<Vec<u8>> {
+    check_whole_blocks(plaintext)?;
+    let mut state = StatefulCBC::new(key, iv);
+    plaintext
+        .chunks(16)
+        .map(|block| state.encrypt_block(block))
+        .flatten_ok()
+        .collect()
+}
+
+/// Decrypts with AES-128 in CBC mode. The ciphertext has to be whole blocks.
+pub fn decrypt_aes_cbc(
+    ciphertext: &[u8],
+    key: &[u8],
+    iv: Option<Vec<u8>>,
+) -> error::Result<Vec<u8>> {
+    check_whole_blocks(ciphertext)?;
+    let mut state = StatefulCBC::new(key, iv);
+    ciphertext
+        .chunks(16)
+        .map(|block| state.decrypt_block(block))
+        .flatten_ok()
+        .collect()
+}
+
+/// Computes the AES-CBC-MAC of a message, i.e., the last block of its CBC encryption.
+/// If no IV is given, a fixed all-zero IV is used.
+pub fn cbc_mac(message: &[u8], key: &[u8], iv: Option<Vec<u8>>) -> error::Result<Vec<u8>> {
+    let mut state = StatefulCBC::new(key, iv);
+    let mut mac = vec![];
+    for block in padding::pkcs7_pad(message).chunks(16) {
+        mac = state.encrypt_block(block)?;
+    }
+
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_aes_ecb_works() {
+        let plaintext = "The quick brown?".as_bytes();
+        let key = "secret (16bytes)".as_bytes();
+        assert_eq!(
+            plaintext,
+            decrypt_aes_ecb(&encrypt_aes_ecb(plaintext, key).unwrap(), key).unwrap()
+        )
+    }
+
+    #[test]
+    fn encrypt_aes_cbc_then_decrypt_works() {
+        let plaintext = "what even is 16b among friends??".as_bytes();
+        let key = "here are 16b sir".as_bytes();
+        let ciphertext = encrypt_aes_cbc(plaintext, key, None).unwrap();
+        let result = decrypt_aes_cbc(&ciphertext, key, None).unwrap();
+
+        assert_eq!(plaintext, result);
+
+        let iv = random_aes_key();
+        let ciphertext = encrypt_aes_cbc(plaintext, key, Some(iv.clone())).unwrap();
+        assert_eq!(
+            plaintext,
+            decrypt_aes_cbc(&ciphertext, key, Some(iv)).unwrap()
+        );
+    }
+
+    #[test]
+    fn partial_blocks_are_rejected() {
+        let key = "secret (16bytes)".as_bytes();
+        let text = [0x2a; 20];
+        let mismatch = |result| {
+            matches!(
+                result,
+                Err(CryptopalsError::LengthMismatch {
+                    expected: 32,
+                    actual: 20
+                })
+            )
+        };
+
+        assert!(mismatch(encrypt_aes_ecb(&text, key)));
+        assert!(mismatch(decrypt_aes_ecb(&text, key)));
+        assert!(mismatch(encrypt_aes_cbc(&text, key, None)));
+        assert!(mismatch(decrypt_aes_cbc(&text, key, None)));
+    }
+
+    #[test]
+    fn decrypt_aes_ecb_works_on_long_input() {
+        let plaintext = vec![0x2a; 4096];
+        let key = "secret (16bytes)".as_bytes();
+        assert_eq!(
+            plaintext,
+            decrypt_aes_ecb(&encrypt_aes_ecb(&plaintext, key).unwrap(), key).unwrap()
+        )
+    }
+}
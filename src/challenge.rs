@@ -0,0 +1,284 @@
+use std::{fmt, path::Path};
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::{encoding, error, input::Input, json::Json};
+
+/// A challenge that can be solved, and checked against its known answer.
+///
+/// Every `src/setN/challNN.rs` is declared as module `setN::challNN` by the build script, which
+/// also registers the `ChallNN` implementing this in it: adding a challenge is just adding its
+/// file.
+pub trait Challenge: Sync {
+    fn number(&self) -> u8;
+
+    /// The set it belongs to: the `setN/` directory its file is in.
+    fn set(&self) -> u8 {
+        SETS.iter()
+            .find(|&&(number, _)| number == self.number())
+            .map(|&(_, set)| set)
+            .expect("Every challenge is registered by the build script")
+    }
+
+    fn title(&self) -> &'static str;
+
+    /// Whether it takes too long to run along with every other challenge by default.
+    ///
+    /// The build script has to know this too, to ignore the challenge's self-check test, so a
+    /// challenge that overrides it declares `const SLOW: bool = true;` at the top level of its
+    /// module and returns `SLOW` here.
+    fn slow(&self) -> bool {
+        false
+    }
+
+    fn solve(&self, input: &Input) -> error::Result<Output>;
+
+    /// What solving it on [`Challenge::default_input`] has to find: the fields of its output that
+    /// don't change from run to run.
+    fn expected(&self) -> Output;
+
+    /// The challenge's own input, `data/<number>.txt` in the crate's directory, or no input if
+    /// it doesn't have any.
+    fn default_input(&self) -> Input {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join(format!("{}.txt", self.number()));
+        if path.exists() {
+            Input::file(path)
+        } else {
+            Input::bytes(vec![])
+        }
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/registry.rs"));
+
+/// Every challenge, in order.
+pub fn all() -> &'static [&'static dyn Challenge] {
+    CHALLENGES
+}
+
+pub fn find(number: u8) -> Option<&'static dyn Challenge> {
+    CHALLENGES
+        .iter()
+        .copied()
+        .find(|challenge| challenge.number() == number)
+}
+
+/// A value a challenge found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bytes(Vec<u8>),
+    Text(String),
+    Number(BigInt),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "{}", encoding::bytes_to_hex(bytes)),
+            Self::Text(text) => write!(f, "{text}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl Value {
+    /// Bytes become both their hex and their (lossy) UTF-8, to be read either way.
+    pub fn to_json(&self) -> Json {
+        match self {
+            Self::Bytes(bytes) => Json::object([
+                ("hex", Json::string(encoding::bytes_to_hex(bytes))),
+                ("utf8", Json::string(String::from_utf8_lossy(bytes))),
+            ]),
+            Self::Text(text) => Json::string(text.as_str()),
+            Self::Number(n) => Json::number(n),
+            Self::Bool(b) => Json::Bool(*b),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(value.to_vec())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<BigUint> for Value {
+    fn from(value: BigUint) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+/// What solving a challenge found, as labelled values in the order they were found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Output {
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl Output {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, label: &'static str, value: impl Into<Value>) -> Self {
+        self.push(label, value);
+        self
+    }
+
+    pub fn push(&mut self, label: &'static str, value: impl Into<Value>) {
+        self.fields.push((label, value.into()));
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, value)| value)
+    }
+
+    pub fn fields(&self) -> &[(&'static str, Value)] {
+        &self.fields
+    }
+
+    /// An object with a snake_case key for each label, e.g. `bobs_public_key` for "Bob's public
+    /// key".
+    pub fn to_json(&self) -> Json {
+        Json::Object(
+            self.fields
+                .iter()
+                .map(|(label, value)| (json_key(label), value.to_json()))
+                .collect(),
+        )
+    }
+
+    /// Whether every field of `expected` is in this output, with the same value.
+    pub fn matches(&self, expected: &Output) -> bool {
+        expected
+            .fields
+            .iter()
+            .all(|(label, value)| self.get(label) == Some(value))
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, value) in &self.fields {
+            writeln!(f, "{label}: {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn json_key(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| *c != '\'')
+        .collect::<String>()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Solves `challenge` on its own input, and checks that it was registered under the right number
+/// and found its known answer.
+#[cfg(test)]
+fn self_check(challenge: &dyn Challenge, number: u8, set: u8) {
+    assert_eq!(number, challenge.number());
+    assert_eq!(set, challenge.set());
+
+    let output = challenge
+        .solve(&challenge.default_input())
+        .unwrap_or_else(|e| panic!("Failed to solve: {e}"));
+    let expected = challenge.expected();
+    assert!(
+        output.matches(&expected),
+        "Expected:\n{expected}\nGot:\n{output}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_in_order() {
+        let numbers: Vec<_> = all().iter().map(|challenge| challenge.number()).collect();
+        let mut sorted = numbers.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, numbers);
+        assert_eq!(Some(8), find(57).map(|challenge| challenge.set()));
+        assert_eq!(Some(8), find(65).map(|challenge| challenge.set()));
+    }
+
+    #[test]
+    fn output_matches_works() {
+        let output = Output::new()
+            .with("Key", vec![0x58])
+            .with("ASCII", "Cooking MC's like a pound of bacon");
+        assert!(output.matches(&Output::new().with("Key", vec![0x58])));
+        assert!(!output.matches(&Output::new().with("Key", vec![0x59])));
+        assert!(!output.matches(&Output::new().with("Matches", true)));
+        assert_eq!(
+            "Key: 58\nASCII: Cooking MC's like a pound of bacon\n",
+            output.to_string()
+        );
+    }
+
+    #[test]
+    fn output_to_json_works() {
+        let output = Output::new()
+            .with("Key", vec![0x35])
+            .with("Bob's public key", 42_usize)
+            .with("Index of y in [0, 2^20]", true);
+        assert_eq!(
+            concat!(
+                r#"{"key":{"hex":"35","utf8":"5"},"bobs_public_key":42,"#,
+                r#""index_of_y_in_0_2_20":true}"#
+            ),
+            output.to_json().to_string()
+        );
+    }
+}
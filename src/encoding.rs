@@ -0,0 +1,116 @@
+use std::fmt::Write;
+
+use crate::{
+    base64,
+    error::{self, CryptopalsError},
+};
+
+/// Converts a hex sequence into a vector of bytes.
+///
+/// Fails with the position of the first character that isn't a hex digit, or the length of the
+/// sequence if it has an odd number of them.
+pub fn hex_to_bytes(line: &str) -> error::Result<Vec<u8>> {
+    let digit = |position: usize| {
+        line.as_bytes()
+            .get(position)
+            .and_then(|&c| char::from(c).to_digit(16))
+            .ok_or(CryptopalsError::InvalidHex {
+                position: position.min(line.len()),
+            })
+    };
+
+    (0..line.len())
+        .step_by(2)
+        .map(|i| Ok((digit(i)? << 4 | digit(i + 1)?) as u8))
+        .collect()
+}
+
+/// Converts a vector of bytes into a hex sequence.
+pub fn bytes_to_hex(seq: &[u8]) -> String {
+    seq.iter().fold(String::new(), |mut out, val| {
+        write!(out, "{val:02x}").unwrap();
+        out
+    })
+}
+
+/// Encodes bytes as base64, with padding.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    base64::STANDARD.encode(bytes)
+}
+
+/// Decodes base64, ignoring anything that isn't a base64 character (such as line breaks).
+///
+/// Fails with the position of misplaced padding, or the length of the input if the last group is
+/// incomplete. See [`base64`] for strict, URL-safe and unpadded decoding.
+pub fn base64_decode(input: &str) -> error::Result<Vec<u8>> {
+    base64::STANDARD.decode_lenient(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_to_bytes_reports_position() {
+        assert_eq!(vec![0x1c, 0xff], hex_to_bytes("1cFf").unwrap());
+        assert!(matches!(
+            hex_to_bytes("1c0g"),
+            Err(CryptopalsError::InvalidHex { position: 3 })
+        ));
+        assert!(matches!(
+            hex_to_bytes("1c0"),
+            Err(CryptopalsError::InvalidHex { position: 3 })
+        ));
+    }
+
+    #[test]
+    fn base64_encode_works() {
+        assert_eq!("bGlnaHQgd29yay4=", base64_encode(b"light work."));
+        assert_eq!("bGlnaHQgd29yaw==", base64_encode(b"light work"));
+        assert_eq!("bGlnaHQgd29y", base64_encode(b"light wor"));
+    }
+
+    #[test]
+    fn base64_decode_works() {
+        assert_eq!(
+            "light work.".as_bytes(),
+            base64_decode("bGlnaHQgd29yay4=").unwrap(),
+        );
+        assert_eq!(
+            "light work".as_bytes(),
+            base64_decode("bGlnaHQgd29yaw==").unwrap(),
+        );
+        assert_eq!(
+            "light wor".as_bytes(),
+            base64_decode("bGlnaHQgd29y").unwrap(),
+        );
+    }
+
+    #[test]
+    fn base64_decode_ignores_unknown_symbols() {
+        assert_eq!(
+            "light work".as_bytes(),
+            base64_decode("b__Glna$$$HQ€ €gd2## ##9y?aääääw==").unwrap(),
+        );
+    }
+
+    #[test]
+    fn base64_decode_rejects_malformed() {
+        assert!(matches!(
+            base64_decode("bGl"),
+            Err(CryptopalsError::InvalidBase64 { position: 3 })
+        ));
+        assert!(matches!(
+            base64_decode("bG=n"),
+            Err(CryptopalsError::InvalidBase64 { position: 3 })
+        ));
+        assert!(matches!(
+            base64_decode("b==="),
+            Err(CryptopalsError::InvalidBase64 { position: 3 })
+        ));
+        assert!(matches!(
+            base64_decode("$abc"),
+            Err(CryptopalsError::InvalidBase64 { position: 4 })
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use std::{error, fmt, io};
+
+use openssl::error::ErrorStack;
+
+/// Everything that can go wrong in this crate, with enough detail to tell why input was rejected.
+#[derive(Debug)]
+pub enum CryptopalsError {
+    /// Not a hex digit at this (byte) position, or the position at the end where one is missing.
+    InvalidHex {
+        position: usize,
+    },
+    /// Not a base64 character at this (byte) position, or misplaced padding, or the position at
+    /// the end where characters are missing.
+    InvalidBase64 {
+        position: usize,
+    },
+    /// Invalid PKCS#7 padding.
+    BadPadding,
+    /// Input lacking something that's needed, such as an operand.
+    MissingInput(&'static str),
+    /// A parameter outside of the range it has to be in.
+    InvalidParameter(&'static str),
+    /// Valid input, but not something this implementation handles.
+    Unsupported(&'static str),
+    /// An attack ran through without finding what it was after, e.g. as an oracle doesn't behave
+    /// as expected.
+    AttackFailed(&'static str),
+    /// Input of the wrong length: `expected` is the length it needed to have, or the most it
+    /// could have.
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    OpenSsl(ErrorStack),
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, CryptopalsError>;
+
+impl fmt::Display for CryptopalsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHex { position } => write!(f, "Invalid hex at position {position}"),
+            Self::InvalidBase64 { position } => write!(f, "Invalid base64 at position {position}"),
+            Self::BadPadding => write!(f, "Bad padding"),
+            Self::MissingInput(what) => write!(f, "Missing input: {what}"),
+            Self::InvalidParameter(what) => write!(f, "Invalid parameter: {what}"),
+            Self::Unsupported(what) => write!(f, "Unsupported: {what}"),
+            Self::AttackFailed(why) => write!(f, "Attack failed: {why}"),
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "Length mismatch: expected {expected}, got {actual}")
+            }
+            Self::OpenSsl(e) => write!(f, "OpenSSL error: {e}"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl error::Error for CryptopalsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OpenSsl(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for CryptopalsError {
+    fn from(e: ErrorStack) -> Self {
+        Self::OpenSsl(e)
+    }
+}
+
+impl From<io::Error> for CryptopalsError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,534 @@
+use std::ops::{Add, Mul};
+
+use rand::Rng;
+
+use crate::gf2::BitMatrix;
+
+/// Element of GF(2^128) as used by GCM: polynomials over GF(2) modulo `x^128 + x^7 + x^2 + x + 1`,
+/// with the coefficient of `x^0` in the most significant bit of a big-endian block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Gf128(pub u128);
+
+/// The reduction polynomial, without its `x^128` term, in GCM's bit order.
+const R: u128 = 0xe1 << 120;
+
+impl Gf128 {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << 127);
+
+    /// Reads a block of up to 16 bytes, zero-padded on the right.
+    pub fn from_block(block: &[u8]) -> Self {
+        let mut bytes = [0; 16];
+        bytes[..block.len()].copy_from_slice(block);
+
+        Self(u128::from_be_bytes(bytes))
+    }
+
+    pub fn to_block(self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn random() -> Self {
+        Self(rand::thread_rng().gen())
+    }
+
+    pub fn square(self) -> Self {
+        self * self
+    }
+
+    pub fn pow(self, exponent: u128) -> Self {
+        (0..128).rev().fold(Self::ONE, |acc, i| {
+            let acc = acc.square();
+            if exponent >> i & 1 == 1 {
+                acc * self
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// Multiplicative inverse, which is `a^(2^128 - 2)`. Zero has none, and gives zero.
+    pub fn inverse(self) -> Self {
+        self.pow(u128::MAX - 1)
+    }
+
+    /// Square root, which is `a^(2^127)`, since squaring is a bijection here.
+    pub fn sqrt(self) -> Self {
+        (0..127).fold(self, |acc, _| acc.square())
+    }
+
+    /// The element as a vector over GF(2), its `i`-th entry being the coefficient of `x^i`.
+    pub fn to_bits(self) -> Vec<bool> {
+        (0..128).map(|i| self.0 >> (127 - i) & 1 == 1).collect()
+    }
+
+    pub fn from_bits(bits: &[bool]) -> Self {
+        Self(
+            bits.iter()
+                .enumerate()
+                .filter(|(_, &bit)| bit)
+                .fold(0, |acc, (i, _)| acc | 1 << (127 - i)),
+        )
+    }
+
+    /// `x^i`, whose bit vector is the `i`-th unit vector.
+    pub fn unit(i: usize) -> Self {
+        Self(1 << (127 - i))
+    }
+
+    /// The matrix of the (GF(2)-linear) map `a -> c * a`, on bit vectors.
+    pub fn multiplication_matrix(self) -> BitMatrix {
+        let columns: Vec<_> = (0..128).map(|i| (self * Self::unit(i)).to_bits()).collect();
+
+        BitMatrix::from_columns(128, &columns)
+    }
+
+    /// The matrix of the map `a -> a^2`, which is GF(2)-linear too in characteristic 2.
+    pub fn squaring_matrix() -> BitMatrix {
+        let columns: Vec<_> = (0..128).map(|i| Self::unit(i).square().to_bits()).collect();
+
+        BitMatrix::from_columns(128, &columns)
+    }
+}
+
+/// Multiples of a fixed element by every possible byte in every position, so that multiplying by
+/// it takes one table lookup per byte instead of going bit by bit.
+pub struct MulTable {
+    table: Vec<[Gf128; 256]>,
+}
+
+impl MulTable {
+    pub fn new(h: Gf128) -> Self {
+        let table = (0..16)
+            .map(|i| {
+                let mut row = [Gf128::ZERO; 256];
+                for byte in 1..256usize {
+                    // multiplication is linear, so split off the lowest bit
+                    let low = byte & byte.wrapping_neg();
+                    row[byte] = if low == byte {
+                        Gf128((byte as u128) << (120 - 8 * i)) * h
+                    } else {
+                        row[byte - low] + row[low]
+                    };
+                }
+
+                row
+            })
+            .collect();
+
+        Self { table }
+    }
+
+    pub fn mul(&self, a: Gf128) -> Gf128 {
+        a.to_block()
+            .iter()
+            .zip(&self.table)
+            .fold(Gf128::ZERO, |acc, (&byte, row)| acc + row[byte as usize])
+    }
+}
+
+impl Add for Gf128 {
+    type Output = Self;
+
+    // adding polynomials over GF(2) is XORing their coefficients
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl Mul for Gf128 {
+    type Output = Self;
+
+    /// Shift-and-add multiplication, as in the GCM specification.
+    fn mul(self, rhs: Self) -> Self {
+        let mut product = 0;
+        let mut v = rhs.0;
+
+        for i in (0..128).rev() {
+            if self.0 >> i & 1 == 1 {
+                product ^= v;
+            }
+            v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+        }
+
+        Self(product)
+    }
+}
+
+/// Polynomial over GF(2^128), with its coefficients from the lowest degree up (and never any
+/// leading zeros, so the zero polynomial has none at all).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<Gf128>,
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Gf128>) -> Self {
+        while coefficients.last() == Some(&Gf128::ZERO) {
+            coefficients.pop();
+        }
+
+        Self { coefficients }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(vec![])
+    }
+
+    pub fn one() -> Self {
+        Self::new(vec![Gf128::ONE])
+    }
+
+    pub fn x() -> Self {
+        Self::new(vec![Gf128::ZERO, Gf128::ONE])
+    }
+
+    pub fn coefficients(&self) -> &[Gf128] {
+        &self.coefficients
+    }
+
+    /// Degree of the polynomial, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.coefficients == [Gf128::ONE]
+    }
+
+    fn leading(&self) -> Gf128 {
+        self.coefficients.last().copied().unwrap_or(Gf128::ZERO)
+    }
+
+    /// Divides the polynomial by its leading coefficient.
+    pub fn monic(&self) -> Self {
+        let inverse = self.leading().inverse();
+
+        Self::new(self.coefficients.iter().map(|&c| c * inverse).collect())
+    }
+
+    pub fn evaluate(&self, x: Gf128) -> Gf128 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Gf128::ZERO, |acc, &c| acc * x + c)
+    }
+
+    pub fn square(&self) -> Self {
+        // cross terms come in pairs, which cancel out in characteristic 2
+        Self::new(
+            self.coefficients
+                .iter()
+                .flat_map(|&c| [c.square(), Gf128::ZERO])
+                .collect(),
+        )
+    }
+
+    /// Euclidean division, returning the quotient and the remainder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the divisor is zero.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let Some(degree) = divisor.degree() else {
+            panic!("Division by the zero polynomial");
+        };
+        if self.coefficients.len() <= degree {
+            return (Self::zero(), self.clone());
+        }
+
+        let inverse = divisor.leading().inverse();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Gf128::ZERO; remainder.len() - degree];
+        for i in (0..quotient.len()).rev() {
+            let c = remainder[i + degree] * inverse;
+            quotient[i] = c;
+            for (r, &d) in remainder[i..].iter_mut().zip(&divisor.coefficients) {
+                *r = *r + c * d;
+            }
+        }
+        remainder.truncate(degree);
+
+        (Self::new(quotient), Self::new(remainder))
+    }
+
+    fn rem(&self, divisor: &Self) -> Self {
+        self.div_rem(divisor).1
+    }
+
+    /// Monic greatest common divisor (zero only if both polynomials are).
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            (a, b) = (b.clone(), a.rem(&b));
+        }
+
+        a.monic()
+    }
+
+    pub fn derivative(&self) -> Self {
+        // i * c is c for odd i and 0 for even i
+        Self::new(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, &c)| if i % 2 == 1 { c } else { Gf128::ZERO })
+                .collect(),
+        )
+    }
+
+    /// Square root of a polynomial whose odd coefficients are all zero (so its derivative is zero).
+    fn sqrt(&self) -> Self {
+        Self::new(
+            self.coefficients
+                .iter()
+                .step_by(2)
+                .map(|c| c.sqrt())
+                .collect(),
+        )
+    }
+
+    /// `self^(2^128) mod modulus`, the Frobenius map.
+    fn frobenius_mod(&self, modulus: &Self) -> Self {
+        (0..128).fold(self.rem(modulus), |acc, _| acc.square().rem(modulus))
+    }
+
+    /// Splits a nonzero polynomial into monic square-free factors, each with its multiplicity.
+    pub fn square_free_factorization(&self) -> Vec<(Self, usize)> {
+        let f = self.monic();
+        let mut factors = vec![];
+
+        // w is the product of the irreducible factors with an odd multiplicity of at least i, and
+        // taking them out of c one multiplicity at a time leaves only the others (as a square)
+        let mut c = f.gcd(&f.derivative());
+        let mut w = f.div_rem(&c).0;
+        let mut i = 1;
+        while !w.is_one() {
+            let y = w.gcd(&c);
+            let factor = w.div_rem(&y).0;
+            if !factor.is_one() {
+                factors.push((factor, i));
+            }
+
+            c = c.div_rem(&y).0;
+            w = y;
+            i += 1;
+        }
+
+        // whatever is left is a square
+        if !c.is_one() {
+            let roots = c.sqrt().square_free_factorization();
+            factors.extend(roots.into_iter().map(|(g, m)| (g, 2 * m)));
+        }
+
+        factors
+    }
+
+    /// Splits a square-free polynomial into the products of its irreducible factors of each
+    /// degree, returned with that degree.
+    pub fn distinct_degree_factorization(&self) -> Vec<(Self, usize)> {
+        let mut f = self.monic();
+        let mut factors = vec![];
+
+        // the irreducible polynomials of degree i are the factors of x^(q^i) - x
+        let mut h = Self::x();
+        let mut i = 1;
+        while f.degree().unwrap_or(0) >= 2 * i {
+            h = h.frobenius_mod(self);
+            let g = f.gcd(&(&h + &Self::x()));
+            if !g.is_one() {
+                f = f.div_rem(&g).0;
+                factors.push((g, i));
+            }
+            i += 1;
+        }
+
+        if let Some(degree @ 1..) = f.degree() {
+            factors.push((f, degree));
+        }
+
+        factors
+    }
+
+    /// Splits a product of distinct irreducible polynomials of the given degree into them, with
+    /// Cantor-Zassenhaus (randomized).
+    pub fn equal_degree_factorization(&self, degree: usize) -> Vec<Self> {
+        let n = self.degree().unwrap_or(0);
+        let mut factors = vec![self.monic()];
+
+        while factors.len() < n / degree {
+            // the trace of a random element, a + a^2 + ... + a^(2^(128d - 1)), is either 0 or 1
+            // modulo each factor (at random), so its gcd with them splits them apart
+            let a = Self::new((0..n).map(|_| Gf128::random()).collect());
+            let mut power = a.rem(self);
+            let mut trace = power.clone();
+            for _ in 1..128 * degree {
+                power = power.square().rem(self);
+                trace = &trace + &power;
+            }
+
+            factors = factors
+                .into_iter()
+                .flat_map(|u| {
+                    let g = u.gcd(&trace);
+                    if u.degree() == Some(degree) || g.is_one() || g == u {
+                        vec![u]
+                    } else {
+                        let rest = u.div_rem(&g).0;
+                        vec![g, rest]
+                    }
+                })
+                .collect();
+        }
+
+        factors
+    }
+
+    /// Factors a nonzero polynomial into monic irreducible ones, each with its multiplicity.
+    pub fn factor(&self) -> Vec<(Self, usize)> {
+        self.square_free_factorization()
+            .into_iter()
+            .flat_map(|(g, multiplicity)| {
+                g.distinct_degree_factorization()
+                    .into_iter()
+                    .flat_map(|(h, degree)| h.equal_degree_factorization(degree))
+                    .map(move |irreducible| (irreducible, multiplicity))
+            })
+            .collect()
+    }
+
+    /// Distinct roots of a nonzero polynomial.
+    pub fn roots(&self) -> Vec<Gf128> {
+        self.factor()
+            .into_iter()
+            .filter(|(factor, _)| factor.degree() == Some(1))
+            .map(|(factor, _)| factor.coefficients[0])
+            .collect()
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: Self) -> Polynomial {
+        let len = self.coefficients.len().max(rhs.coefficients.len());
+        let coefficient = |p: &Polynomial, i| p.coefficients.get(i).copied().unwrap_or_default();
+
+        Polynomial::new(
+            (0..len)
+                .map(|i| coefficient(self, i) + coefficient(rhs, i))
+                .collect(),
+        )
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Self) -> Polynomial {
+        if self.is_zero() || rhs.is_zero() {
+            return Polynomial::zero();
+        }
+
+        let mut product = vec![Gf128::ZERO; self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            for (j, &b) in rhs.coefficients.iter().enumerate() {
+                product[i + j] = product[i + j] + a * b;
+            }
+        }
+
+        Polynomial::new(product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gf128_works() {
+        // from the GCM specification, test case 2: X_1 = C * H
+        let h = Gf128(0x66e94bd4ef8a2c3b884cfa59ca342b2e);
+        let c = Gf128(0x0388dace60b6a392f328c2b971b2fe78);
+        assert_eq!(Gf128(0x5e2ec746917062882c85b0685353deb7), c * h);
+
+        let a = Gf128::random();
+        assert_eq!(a, a * Gf128::ONE);
+        assert_eq!(c * h * a, c * (h * a));
+        assert_eq!(Gf128::ONE, a * a.inverse());
+        assert_eq!(a, a.sqrt().square());
+        assert_eq!(a.square() * a, a.pow(3));
+        assert_eq!(a * h, MulTable::new(h).mul(a));
+    }
+
+    #[test]
+    fn matrices_work() {
+        let (a, c) = (Gf128::random(), Gf128::random());
+        assert_eq!(a, Gf128::from_bits(&a.to_bits()));
+        assert_eq!(
+            (c * a).to_bits(),
+            c.multiplication_matrix().mul_vector(&a.to_bits())
+        );
+        assert_eq!(
+            a.square().to_bits(),
+            Gf128::squaring_matrix().mul_vector(&a.to_bits())
+        );
+    }
+
+    /// Multiplies out factors with their multiplicities.
+    fn expand(factors: &[(Polynomial, usize)]) -> Polynomial {
+        factors
+            .iter()
+            .flat_map(|(factor, multiplicity)| std::iter::repeat_n(factor, *multiplicity))
+            .fold(Polynomial::one(), |acc, factor| &acc * factor)
+    }
+
+    #[test]
+    fn factor_works() {
+        let (a, b, c) = (Gf128::random(), Gf128::random(), Gf128::random());
+        let linear = |root| Polynomial::new(vec![root, Gf128::ONE]);
+        let quadratic = Polynomial::new(vec![Gf128::random(), Gf128::random(), Gf128::ONE]);
+        let f = expand(&[
+            (linear(a), 1),
+            (linear(b), 2),
+            (linear(c), 3),
+            (quadratic.clone(), 2),
+        ]);
+
+        let factors = f.factor();
+        assert_eq!(f, expand(&factors));
+        assert!(factors
+            .iter()
+            .all(|(factor, _)| factor.distinct_degree_factorization().len() == 1));
+
+        let roots = f.roots();
+        for root in [a, b, c] {
+            assert!(roots.contains(&root));
+        }
+        for root in roots {
+            assert_eq!(Gf128::ZERO, f.evaluate(root));
+        }
+
+        // the quadratic either splits into two roots or stays whole
+        let quadratic_factors = quadratic.factor();
+        assert_eq!(quadratic, expand(&quadratic_factors));
+        assert!(matches!(quadratic_factors.len(), 1 | 2));
+    }
+
+    #[test]
+    fn polynomial_division_works() {
+        let f = Polynomial::new((0..7).map(|_| Gf128::random()).collect());
+        let g = Polynomial::new((0..3).map(|_| Gf128::random()).collect());
+        let (quotient, remainder) = f.div_rem(&g);
+
+        assert_eq!(f, &(&quotient * &g) + &remainder);
+        assert!(remainder.degree() < g.degree());
+        assert_eq!(g.monic(), (&f * &g).gcd(&g));
+    }
+}
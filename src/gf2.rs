@@ -0,0 +1,303 @@
+use std::ops::{Add, Mul};
+
+/// Matrix over GF(2), with each row packed into 64-bit words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<Vec<u64>>,
+}
+
+impl BitMatrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![vec![0; cols.div_ceil(64)]; rows],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut matrix = Self::zero(n, n);
+        for i in 0..n {
+            matrix.set(i, i, true);
+        }
+
+        matrix
+    }
+
+    /// Builds a matrix from its columns, which must all have the given length.
+    pub fn from_columns(rows: usize, columns: &[Vec<bool>]) -> Self {
+        let mut matrix = Self::zero(rows, columns.len());
+        for (col, column) in columns.iter().enumerate() {
+            assert_eq!(rows, column.len(), "Columns must all have the same length");
+            for (row, &bit) in column.iter().enumerate() {
+                matrix.set(row, col, bit);
+            }
+        }
+
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.data[row][col / 64] >> (col % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        if value {
+            self.data[row][col / 64] |= 1 << (col % 64);
+        } else {
+            self.data[row][col / 64] &= !(1 << (col % 64));
+        }
+    }
+
+    pub fn row(&self, row: usize) -> Vec<bool> {
+        (0..self.cols).map(|col| self.get(row, col)).collect()
+    }
+
+    pub fn column(&self, col: usize) -> Vec<bool> {
+        (0..self.rows).map(|row| self.get(row, col)).collect()
+    }
+
+    /// The matrix made of only the first `rows` rows of this one.
+    pub fn top_rows(&self, rows: usize) -> Self {
+        Self {
+            rows,
+            cols: self.cols,
+            data: self.data[..rows].to_vec(),
+        }
+    }
+
+    /// Adds the rows of another matrix (with as many columns) below the ones of this one.
+    pub fn append_rows(&mut self, other: &Self) {
+        assert_eq!(self.cols, other.cols, "Matrices must have as many columns");
+        self.rows += other.rows;
+        self.data.extend(other.data.iter().cloned());
+    }
+
+    pub fn mul_vector(&self, vector: &[bool]) -> Vec<bool> {
+        assert_eq!(
+            self.cols,
+            vector.len(),
+            "Vector must have as many entries as columns"
+        );
+
+        (0..self.rows)
+            .map(|row| {
+                vector
+                    .iter()
+                    .enumerate()
+                    .filter(|(col, &bit)| bit && self.get(row, *col))
+                    .count()
+                    % 2
+                    == 1
+            })
+            .collect()
+    }
+
+    /// Gaussian elimination into reduced row echelon form, returning the pivot columns (one for
+    /// each of the first rows, the rest being all zero).
+    pub fn reduce(&mut self) -> Vec<usize> {
+        let mut pivots = vec![];
+
+        for col in 0..self.cols {
+            let row = pivots.len();
+            let Some(pivot) = (row..self.rows).find(|&r| self.get(r, col)) else {
+                continue;
+            };
+            self.data.swap(row, pivot);
+
+            let pivot_row = self.data[row].clone();
+            for (r, other) in self.data.iter_mut().enumerate() {
+                if r != row && other[col / 64] >> (col % 64) & 1 == 1 {
+                    for (word, pivot_word) in other.iter_mut().zip(&pivot_row).skip(col / 64) {
+                        *word ^= pivot_word;
+                    }
+                }
+            }
+
+            pivots.push(col);
+            if pivots.len() == self.rows {
+                break;
+            }
+        }
+
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduce().len()
+    }
+
+    /// Basis of the kernel (or null space): the vectors `v` such that `Av = 0`.
+    pub fn kernel(&self) -> Vec<Vec<bool>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduce();
+
+        // each free column gives a basis vector, setting it to 1 and the other free ones to 0,
+        // which then fixes the pivot ones
+        (0..self.cols)
+            .filter(|col| !pivots.contains(col))
+            .map(|free| {
+                let mut vector = vec![false; self.cols];
+                vector[free] = true;
+                for (row, &pivot) in pivots.iter().enumerate() {
+                    vector[pivot] = reduced.get(row, free);
+                }
+
+                vector
+            })
+            .collect()
+    }
+
+    /// Some solution `v` of `Av = target`, if there is any.
+    pub fn solve(&self, target: &[bool]) -> Option<Vec<bool>> {
+        assert_eq!(
+            self.rows,
+            target.len(),
+            "Target must have as many entries as rows"
+        );
+
+        // reduce the augmented matrix: there's no solution if the target column gets a pivot,
+        // and otherwise setting the free entries to 0 fixes the pivot ones
+        let mut augmented = Self::zero(self.rows, self.cols + 1);
+        for (row, &bit) in target.iter().enumerate() {
+            augmented.data[row][..self.data[row].len()].copy_from_slice(&self.data[row]);
+            augmented.set(row, self.cols, bit);
+        }
+        let pivots = augmented.reduce();
+        if pivots.last() == Some(&self.cols) {
+            return None;
+        }
+
+        let mut solution = vec![false; self.cols];
+        for (row, &pivot) in pivots.iter().enumerate() {
+            solution[pivot] = augmented.get(row, self.cols);
+        }
+
+        Some(solution)
+    }
+}
+
+// addition in GF(2) is XOR
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Add for &BitMatrix {
+    type Output = BitMatrix;
+
+    fn add(self, rhs: Self) -> BitMatrix {
+        assert_eq!(
+            (self.rows, self.cols),
+            (rhs.rows, rhs.cols),
+            "Matrix dimensions must match"
+        );
+
+        let mut sum = self.clone();
+        for (row, rhs_row) in sum.data.iter_mut().zip(&rhs.data) {
+            for (word, rhs_word) in row.iter_mut().zip(rhs_row) {
+                *word ^= rhs_word;
+            }
+        }
+
+        sum
+    }
+}
+
+impl Mul for &BitMatrix {
+    type Output = BitMatrix;
+
+    fn mul(self, rhs: Self) -> BitMatrix {
+        assert_eq!(self.cols, rhs.rows, "Matrix dimensions must match");
+
+        // each row of the product is the sum of the rows of rhs picked out by a row of self
+        let mut product = BitMatrix::zero(self.rows, rhs.cols);
+        for (row, product_row) in product.data.iter_mut().enumerate() {
+            for (col, rhs_row) in rhs.data.iter().enumerate() {
+                if self.get(row, col) {
+                    for (word, rhs_word) in product_row.iter_mut().zip(rhs_row) {
+                        *word ^= rhs_word;
+                    }
+                }
+            }
+        }
+
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_matrix(rows: &[&[u8]]) -> BitMatrix {
+        let columns: Vec<Vec<bool>> = (0..rows[0].len())
+            .map(|col| rows.iter().map(|row| row[col] == 1).collect())
+            .collect();
+
+        BitMatrix::from_columns(rows.len(), &columns)
+    }
+
+    #[test]
+    fn reduce_works() {
+        let mut matrix = to_matrix(&[&[0, 1, 1, 0], &[1, 1, 0, 1], &[1, 0, 1, 1]]);
+        let pivots = matrix.reduce();
+
+        assert_eq!(vec![0, 1], pivots);
+        assert_eq!(
+            to_matrix(&[&[1, 0, 1, 1], &[0, 1, 1, 0], &[0, 0, 0, 0]]),
+            matrix
+        );
+        assert_eq!(3, BitMatrix::identity(3).rank());
+    }
+
+    #[test]
+    fn kernel_works() {
+        let matrix = to_matrix(&[&[0, 1, 1, 0], &[1, 1, 0, 1], &[1, 0, 1, 1]]);
+        let kernel = matrix.kernel();
+
+        assert_eq!(2, kernel.len());
+        for vector in &kernel {
+            assert_eq!(vec![false; 3], matrix.mul_vector(vector));
+        }
+        assert!(BitMatrix::identity(5).kernel().is_empty());
+    }
+
+    #[test]
+    fn solve_works() {
+        let matrix = to_matrix(&[&[0, 1, 1, 0], &[1, 1, 0, 1], &[1, 0, 1, 1]]);
+        let target = vec![true, false, true];
+        let solution = matrix.solve(&target).unwrap();
+
+        assert_eq!(target, matrix.mul_vector(&solution));
+        // the last row is the sum of the others, so the target must be too
+        assert_eq!(None, matrix.solve(&[true, false, false]));
+    }
+
+    #[test]
+    fn mul_works() {
+        let a = to_matrix(&[&[1, 1, 0], &[0, 1, 1]]);
+        let b = to_matrix(&[&[1, 0], &[1, 1], &[0, 1]]);
+
+        assert_eq!(to_matrix(&[&[0, 1], &[1, 0]]), &a * &b);
+        assert_eq!(a, &BitMatrix::identity(2) * &a);
+        assert_eq!(BitMatrix::zero(2, 3), &a + &a);
+
+        // wider than a word, to cross word boundaries
+        let wide = BitMatrix::from_columns(
+            2,
+            &(0..100)
+                .map(|i| vec![i % 3 == 0, i % 7 == 0])
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(wide, &BitMatrix::identity(2) * &wide);
+        assert_eq!(wide, &wide * &BitMatrix::identity(100));
+        assert_eq!(2, wide.rank());
+    }
+}
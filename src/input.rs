@@ -0,0 +1,165 @@
+use std::{
+    cell::OnceCell,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use crate::{encoding, error};
+
+/// How a challenge's input is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Hex,
+    Base64,
+    Raw,
+}
+
+impl Format {
+    /// Parses a format by its name on the command line (`hex`, `base64` or `raw`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hex" => Some(Self::Hex),
+            "base64" => Some(Self::Base64),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    fn decode(self, text: &[u8]) -> error::Result<Vec<u8>> {
+        match self {
+            Self::Hex => encoding::hex_to_bytes(String::from_utf8_lossy(text).trim()),
+            Self::Base64 => encoding::base64_decode(&String::from_utf8_lossy(text)),
+            Self::Raw => Ok(text.to_vec()),
+        }
+    }
+}
+
+enum Source {
+    Stdin,
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// Input to a challenge: where to read it from (only once a challenge asks for it), how to decode
+/// it if not in the challenge's usual format, and a key to use instead of the challenge's own.
+pub struct Input {
+    source: Source,
+    format: Option<Format>,
+    key: Option<Vec<u8>>,
+    data: OnceCell<Vec<u8>>,
+}
+
+impl Input {
+    pub fn stdin() -> Self {
+        Self::from_source(Source::Stdin)
+    }
+
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::from_source(Source::File(path.into()))
+    }
+
+    pub fn bytes(data: Vec<u8>) -> Self {
+        Self::from_source(Source::Bytes(data))
+    }
+
+    fn from_source(source: Source) -> Self {
+        Self {
+            source,
+            format: None,
+            key: None,
+            data: OnceCell::new(),
+        }
+    }
+
+    /// Decodes the input with this format instead of the challenge's usual one.
+    pub fn with_format(self, format: Format) -> Self {
+        Self {
+            format: Some(format),
+            ..self
+        }
+    }
+
+    /// Uses this key instead of the challenge's own.
+    pub fn with_key(self, key: Vec<u8>) -> Self {
+        Self {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    fn read(&self) -> error::Result<&[u8]> {
+        if let Some(data) = self.data.get() {
+            return Ok(data);
+        }
+
+        let data = match &self.source {
+            Source::Stdin => {
+                let mut data = vec![];
+                io::stdin().read_to_end(&mut data)?;
+                data
+            }
+            Source::File(path) => fs::read(path)?,
+            Source::Bytes(data) => data.clone(),
+        };
+
+        Ok(self.data.get_or_init(|| data))
+    }
+
+    /// The whole input, decoded with `format` unless another one was picked.
+    pub fn decoded(&self, format: Format) -> error::Result<Vec<u8>> {
+        self.format.unwrap_or(format).decode(self.read()?)
+    }
+
+    /// Each line of the input, decoded with `format` unless another one was picked.
+    pub fn lines(&self, format: Format) -> error::Result<Vec<Vec<u8>>> {
+        let format = self.format.unwrap_or(format);
+        let data = self.read()?;
+        let data = data.strip_suffix(b"\n").unwrap_or(data);
+        if data.is_empty() {
+            return Ok(vec![]);
+        }
+
+        data.split(|&b| b == b'\n')
+            .map(|line| format.decode(line.strip_suffix(b"\r").unwrap_or(line)))
+            .collect()
+    }
+
+    /// The key that was picked, or else `default`.
+    pub fn key_or(&self, default: &[u8]) -> Vec<u8> {
+        self.key.clone().unwrap_or_else(|| default.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_works() {
+        let input = Input::bytes(b"1c01\r\n\nff\n".to_vec());
+        assert_eq!(
+            vec![vec![0x1c, 0x01], vec![], vec![0xff]],
+            input.lines(Format::Hex).unwrap()
+        );
+
+        let input = input.with_format(Format::Raw);
+        assert_eq!(
+            vec![b"1c01".to_vec(), vec![], b"ff".to_vec()],
+            input.lines(Format::Hex).unwrap()
+        );
+    }
+
+    #[test]
+    fn decoded_works() {
+        let input = Input::bytes(b"SUNF\n".to_vec());
+        assert_eq!(b"ICE".to_vec(), input.decoded(Format::Base64).unwrap());
+        assert_eq!(
+            b"SUNF\n".to_vec(),
+            input
+                .with_format(Format::Raw)
+                .decoded(Format::Base64)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+use crate::error::{self, CryptopalsError};
+
+/// The usual choice for LLL's `delta` parameter: close to 1, for a strongly reduced basis.
+pub const DEFAULT_DELTA: (u32, u32) = (99, 100);
+
+fn dot(a: &[BigInt], b: &[BigInt]) -> BigInt {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// LLL lattice basis reduction, with `delta` given as a fraction `(p, q)` between 1/4 and 1.
+///
+/// This is the integral version (Cohen, Algorithm 2.6.7): rather than the Gram-Schmidt
+/// coefficients themselves, it keeps track of their numerators over the Gram determinants `d_i`,
+/// which are all integers, so everything stays exact without ever needing rationals. Lattices with
+/// rational bases can be scaled up to integral ones first.
+///
+/// Fails if `delta` is out of range, or if the basis vectors are not linearly independent.
+pub fn lll(basis: &[Vec<BigInt>], (p, q): (u32, u32)) -> error::Result<Vec<Vec<BigInt>>> {
+    // 1/4 < p/q <= 1
+    if q == 0 || 4 * u64::from(p) <= u64::from(q) || p > q {
+        return Err(CryptopalsError::InvalidParameter(
+            "LLL's delta must be greater than 1/4 and at most 1",
+        ));
+    }
+    let dependent = CryptopalsError::Unsupported("Basis vectors must be linearly independent");
+
+    let n = basis.len();
+    let mut b = basis.to_vec();
+    if b.iter().any(|v| v.iter().all(Zero::is_zero)) {
+        return Err(dependent);
+    }
+    if n < 2 {
+        return Ok(b);
+    }
+
+    // d[i + 1] is the Gram determinant of the first i + 1 vectors, and lambda[k][j] is the
+    // Gram-Schmidt coefficient mu[k][j] times d[j + 1]
+    let mut d = vec![BigInt::zero(); n + 1];
+    let mut lambda = vec![vec![BigInt::zero(); n]; n];
+    d[0] = 1.into();
+    d[1] = dot(&b[0], &b[0]);
+
+    let (p, q) = (BigInt::from(p), BigInt::from(q));
+    let mut k = 1;
+    let mut k_max = 0;
+    while k < n {
+        if k > k_max {
+            k_max = k;
+            for j in 0..=k {
+                let mut u = dot(&b[k], &b[j]);
+                for i in 0..j {
+                    u = (&d[i + 1] * u - &lambda[k][i] * &lambda[j][i]) / &d[i];
+                }
+
+                if j < k {
+                    lambda[k][j] = u;
+                } else if u.is_zero() {
+                    return Err(dependent);
+                } else {
+                    d[k + 1] = u;
+                }
+            }
+        }
+
+        reduce(&mut b, &mut lambda, &d, k, k - 1);
+
+        // Lovasz condition, multiplied through by the (positive) denominators
+        let lhs = &q * &d[k + 1] * &d[k - 1];
+        let rhs = &p * &d[k] * &d[k] - &q * &lambda[k][k - 1] * &lambda[k][k - 1];
+        if lhs < rhs {
+            swap(&mut b, &mut lambda, &mut d, k, k_max);
+            k = (k - 1).max(1);
+        } else {
+            for l in (0..k - 1).rev() {
+                reduce(&mut b, &mut lambda, &d, k, l);
+            }
+            k += 1;
+        }
+    }
+
+    Ok(b)
+}
+
+/// Size-reduces `b[k]` against `b[l]`.
+fn reduce(b: &mut [Vec<BigInt>], lambda: &mut [Vec<BigInt>], d: &[BigInt], k: usize, l: usize) {
+    if (2u32 * lambda[k][l].abs()) <= d[l + 1] {
+        return;
+    }
+
+    // closest integer to lambda / d
+    let r = (2u32 * &lambda[k][l] + &d[l + 1]).div_floor(&(2u32 * &d[l + 1]));
+    let b_l = b[l].clone();
+    for (x, y) in b[k].iter_mut().zip(b_l) {
+        *x -= &r * y;
+    }
+
+    lambda[k][l] -= &r * &d[l + 1];
+    let (lower, upper) = lambda.split_at_mut(k);
+    for (x, y) in upper[0].iter_mut().zip(&lower[l]).take(l) {
+        *x -= &r * y;
+    }
+}
+
+/// Swaps `b[k]` and `b[k - 1]`, updating the Gram-Schmidt data accordingly.
+fn swap(
+    b: &mut [Vec<BigInt>],
+    lambda: &mut [Vec<BigInt>],
+    d: &mut [BigInt],
+    k: usize,
+    k_max: usize,
+) {
+    b.swap(k, k - 1);
+    let (lower, upper) = lambda.split_at_mut(k);
+    for (x, y) in upper[0].iter_mut().zip(&mut lower[k - 1]).take(k - 1) {
+        std::mem::swap(x, y);
+    }
+
+    let l = lambda[k][k - 1].clone();
+    let new_d = (&d[k - 1] * &d[k + 1] + &l * &l) / &d[k];
+    for row in &mut lambda[k + 1..=k_max] {
+        let t = row[k].clone();
+        row[k] = (&d[k + 1] * &row[k - 1] - &l * &t) / &d[k];
+        row[k - 1] = (&new_d * t + &l * &row[k]) / &d[k + 1];
+    }
+    d[k] = new_d;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_basis(rows: &[&[i64]]) -> Vec<Vec<BigInt>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| BigInt::from(x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lll_works() {
+        let basis = to_basis(&[&[1, 1, 1], &[-1, 0, 2], &[3, 5, 6]]);
+
+        assert_eq!(
+            to_basis(&[&[0, 1, 0], &[1, 0, 1], &[-1, 0, 2]]),
+            lll(&basis, (3, 4)).unwrap()
+        );
+    }
+
+    #[test]
+    fn lll_works_on_scaled_rational_basis() {
+        // the example from challenge 62, with every entry doubled
+        let basis = to_basis(&[
+            &[-4, 0, 4, 0],
+            &[1, -2, 0, 0],
+            &[-2, 0, -4, 1],
+            &[-2, 2, 2, 4],
+        ]);
+
+        assert_eq!(
+            to_basis(&[
+                &[1, -2, 0, 0],
+                &[-2, 0, -4, 1],
+                &[-1, 0, 2, 4],
+                &[-3, -2, 4, 0],
+            ]),
+            lll(&basis, DEFAULT_DELTA).unwrap()
+        );
+    }
+
+    #[test]
+    fn lll_finds_short_vector() {
+        // (1, 1000) is in the lattice, hidden behind a long first basis vector
+        let basis = to_basis(&[&[1, 1000 + 999 * 1_000_003], &[0, 1_000_003]]);
+        let reduced = lll(&basis, DEFAULT_DELTA).unwrap();
+
+        assert!([[1, 1000], [-1, -1000]]
+            .iter()
+            .any(|v| to_basis(&[v])[0] == reduced[0]));
+    }
+
+    #[test]
+    fn lll_rejects_dependent_basis() {
+        for basis in [
+            to_basis(&[&[1, 2, 3], &[2, 4, 6]]),
+            to_basis(&[&[1, 1, 1], &[-1, 0, 2], &[0, 1, 3]]),
+            to_basis(&[&[0, 0]]),
+        ] {
+            assert!(matches!(
+                lll(&basis, DEFAULT_DELTA),
+                Err(CryptopalsError::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn lll_rejects_delta_out_of_range() {
+        let basis = to_basis(&[&[1, 0], &[0, 1]]);
+        for delta in [(1, 0), (0, 1), (1, 4), (101, 100)] {
+            assert!(matches!(
+                lll(&basis, delta),
+                Err(CryptopalsError::InvalidParameter(_))
+            ));
+        }
+        assert!(lll(&basis, (1, 1)).is_ok());
+    }
+}
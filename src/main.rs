@@ -1,34 +1,592 @@
-use std::env;
+use std::{
+    env,
+    io::{self, Write},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
-mod set1;
-mod set2;
-mod util;
+use cryptopals::{
+    attacks, base64, block,
+    challenge::{self, Challenge, Output},
+    encoding, error,
+    input::{Format, Input},
+    json::Json,
+    padding, xor,
+};
 
-fn main() {
-    let chall_nr = env::args()
-        .nth(1)
-        .expect("Challenge number must be passed as argument")
+const USAGE: &str = "\
+Usage: cryptopals run <n> [--input FILE] [--key KEY] [--format hex|base64|raw] [--json]
+       cryptopals list
+       cryptopals run-all [--slow] [--json]
+
+Tools, reading FILE or else stdin, decoded with --format (raw by default):
+       cryptopals xor (--key KEY | --key-hex HEX) [--input FILE] [--format F]
+       cryptopals b64 encode [--url-safe] [--no-padding] [--input FILE]
+       cryptopals b64 decode [--url-safe] [--no-padding] [--strict] [--input FILE]
+       cryptopals hex encode|decode [--input FILE]
+       cryptopals break-single-xor [--lines] [--input FILE] [--format F]
+       cryptopals break-repeating-xor [--input FILE] [--format F]
+       cryptopals detect-ecb [--lines] [--input FILE] [--format F]
+       cryptopals aes ecb|cbc encrypt|decrypt (--key KEY | --key-hex HEX) [--iv HEX]
+                  [--no-padding] [--input FILE] [--format F]
+
+Every tool also takes --json, to print its result as a JSON object instead.";
+
+/// Why a command failed: it was called wrong, or it ran into an error.
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::Usage(message)
+    }
+}
+
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        Self::Usage(message.to_owned())
+    }
+}
+
+impl From<error::CryptopalsError> for CliError {
+    fn from(error: error::CryptopalsError) -> Self {
+        Self::Failed(error.to_string())
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        Self::Failed(error.to_string())
+    }
+}
+
+type CliResult = Result<ExitCode, CliError>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("list") if args.len() == 1 => list(),
+        Some("run-all") => run_all(&args[1..]),
+        Some("xor") => repeating_xor(&args[1..]),
+        Some("b64") => codec(Format::Base64, &args[1..]),
+        Some("hex") => codec(Format::Hex, &args[1..]),
+        Some("break-single-xor") => break_single_xor(&args[1..]),
+        Some("break-repeating-xor") => break_repeating_xor(&args[1..]),
+        Some("detect-ecb") => detect_ecb(&args[1..]),
+        Some("aes") => aes(&args[1..]),
+        // just the challenge number, as before there were subcommands
+        Some(n) if n.parse::<u8>().is_ok() => run(&args),
+        Some(command) => Err(format!("Unknown command: {command}").into()),
+        None => Err("Missing command".into()),
+    };
+
+    result.unwrap_or_else(|error| match error {
+        CliError::Usage(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        CliError::Failed(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    })
+}
+
+fn run(args: &[String]) -> CliResult {
+    let mut args = args.iter();
+    let number: u8 = args
+        .next()
+        .ok_or("Missing challenge number")?
         .parse()
-        .expect("Challenge number must be an integer");
-
-    match chall_nr {
-        1 => set1::chall01::solve_chall01(),
-        2 => set1::chall02::solve_chall02(),
-        3 => set1::chall03::solve_chall03(),
-        4 => set1::chall04::solve_chall04(),
-        5 => set1::chall05::solve_chall05(),
-        6 => set1::chall06::solve_chall06(),
-        7 => set1::chall07::solve_chall07(),
-        8 => set1::chall08::solve_chall08(),
-
-        9 => set2::chall09::solve_chall09(),
-        10 => set2::chall10::solve_chall10(),
-        11 => set2::chall11::solve_chall11(),
-        12 => set2::chall12::solve_chall12(),
-        13 => set2::chall13::solve_chall13(),
-        14 => set2::chall14::solve_chall14(),
-        15 => set2::chall15::solve_chall15(),
-
-        _ => unimplemented!("Unknown challenge number"),
+        .map_err(|_| "Challenge number must be an integer")?;
+    let challenge = challenge::find(number).ok_or(format!("Unknown challenge number: {number}"))?;
+
+    let mut input = Input::stdin();
+    let (mut key, mut format, mut json) = (None, None, false);
+    while let Some(option) = args.next() {
+        if option == "--json" {
+            json = true;
+            continue;
+        }
+
+        let value = args.next().ok_or(format!("Missing value for {option}"))?;
+        match option.as_str() {
+            "--input" => input = Input::file(value),
+            "--key" => key = Some(value.as_bytes().to_vec()),
+            "--format" => {
+                format = Some(Format::from_name(value).ok_or(format!("Unknown format: {value}"))?)
+            }
+            _ => return Err(format!("Unknown option: {option}").into()),
+        }
+    }
+    if let Some(key) = key {
+        input = input.with_key(key);
+    }
+    if let Some(format) = format {
+        input = input.with_format(format);
+    }
+
+    let start = Instant::now();
+    let output = challenge.solve(&input)?;
+    if json {
+        let run = Run {
+            output: Ok(output),
+            passed: None,
+            elapsed: start.elapsed(),
+        };
+        println!("{}", challenge_json(challenge, Some(&run)));
+    } else {
+        print!("{output}");
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn list() -> CliResult {
+    for challenge in challenge::all() {
+        println!(
+            "{:>2}  set {}  {}{}",
+            challenge.number(),
+            challenge.set(),
+            challenge.title(),
+            if challenge.slow() { " (slow)" } else { "" }
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// How running a challenge went.
+struct Run {
+    /// What it found, or why it couldn't.
+    output: error::Result<Output>,
+    /// Whether it found its known answer, if it was checked.
+    passed: Option<bool>,
+    elapsed: Duration,
+}
+
+fn seconds(elapsed: Duration) -> Json {
+    Json::number(format!("{:.6}", elapsed.as_secs_f64()))
+}
+
+/// A challenge's number and title, and how running it went (or that it was skipped).
+fn challenge_json(challenge: &dyn Challenge, run: Option<&Run>) -> Json {
+    let mut fields = vec![
+        ("challenge", Json::number(challenge.number())),
+        ("title", Json::string(challenge.title())),
+    ];
+    match run {
+        Some(run) => {
+            if let Some(passed) = run.passed {
+                fields.push(("status", Json::string(if passed { "pass" } else { "fail" })));
+            }
+            fields.push(("seconds", seconds(run.elapsed)));
+            match &run.output {
+                Ok(output) => fields.push(("output", output.to_json())),
+                Err(e) => fields.push(("error", Json::string(e.to_string()))),
+            }
+        }
+        None => fields.push(("status", Json::string("skip"))),
+    }
+
+    Json::object(fields)
+}
+
+/// Runs every challenge on its own input, and reports which ones found their known answer and how
+/// long they took.
+fn run_all(args: &[String]) -> CliResult {
+    let (mut include_slow, mut json) = (false, false);
+    for flag in args {
+        match flag.as_str() {
+            "--slow" => include_slow = true,
+            "--json" => json = true,
+            _ => return Err(format!("Unknown option: {flag}").into()),
+        }
+    }
+
+    let mut results: Vec<(&dyn Challenge, Option<Run>)> = vec![];
+    for &challenge in challenge::all() {
+        if challenge.slow() && !include_slow {
+            results.push((challenge, None));
+            continue;
+        }
+
+        if !json {
+            println!("== Challenge {}: {}", challenge.number(), challenge.title());
+        }
+        let input = challenge.default_input();
+        let start = Instant::now();
+        let output = challenge.solve(&input);
+        let elapsed = start.elapsed();
+        let passed = output
+            .as_ref()
+            .is_ok_and(|output| output.matches(&challenge.expected()));
+        if !json {
+            match &output {
+                Ok(output) => print!("{output}"),
+                Err(e) => println!("Error: {e}"),
+            }
+            println!();
+        }
+        results.push((
+            challenge,
+            Some(Run {
+                output,
+                passed: Some(passed),
+                elapsed,
+            }),
+        ));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, run)| run.as_ref().is_some_and(|run| run.passed == Some(false)))
+        .count();
+
+    if json {
+        let challenges = results
+            .iter()
+            .map(|(challenge, run)| challenge_json(*challenge, run.as_ref()))
+            .collect();
+        println!(
+            "{}",
+            Json::object([
+                ("challenges", Json::Array(challenges)),
+                ("failed", Json::number(failed)),
+            ])
+        );
+    } else {
+        println!("== Summary");
+        for (challenge, run) in &results {
+            let status = match run {
+                Some(Run {
+                    passed, elapsed, ..
+                }) => format!(
+                    "{}  {:>8.2}s",
+                    if *passed == Some(true) {
+                        "pass"
+                    } else {
+                        "FAIL"
+                    },
+                    elapsed.as_secs_f64()
+                ),
+                None => "skip (slow)".to_owned(),
+            };
+            println!("{:>2}  {status}  {}", challenge.number(), challenge.title());
+        }
+        if failed > 0 {
+            println!("{failed} failed");
+        }
+    }
+
+    if failed > 0 {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Options of the tools, each of which only takes some of them.
+#[derive(Default)]
+struct ToolOptions {
+    input: Option<String>,
+    format: Option<Format>,
+    key: Option<Vec<u8>>,
+    iv: Option<Vec<u8>>,
+    lines: bool,
+    no_padding: bool,
+    url_safe: bool,
+    strict: bool,
+    json: bool,
+}
+
+impl ToolOptions {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(option) = args.next() {
+            if option == "--json" {
+                options.json = true;
+                continue;
+            }
+            if !allowed.contains(&option.as_str()) {
+                return Err(format!("Unknown option: {option}").into());
+            }
+
+            match option.as_str() {
+                "--lines" => options.lines = true,
+                "--no-padding" => options.no_padding = true,
+                "--url-safe" => options.url_safe = true,
+                "--strict" => options.strict = true,
+                _ => {
+                    let value = args.next().ok_or(format!("Missing value for {option}"))?;
+                    match option.as_str() {
+                        "--input" => options.input = Some(value.clone()),
+                        "--format" => {
+                            options.format = Some(
+                                Format::from_name(value)
+                                    .ok_or(format!("Unknown format: {value}"))?,
+                            )
+                        }
+                        "--key" => options.key = Some(value.as_bytes().to_vec()),
+                        "--key-hex" => options.key = Some(parse_hex(option, value)?),
+                        "--iv" => options.iv = Some(parse_hex(option, value)?),
+                        _ => unreachable!("Option allowed but not handled: {option}"),
+                    }
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn input(&self) -> Input {
+        let input = match &self.input {
+            Some(path) => Input::file(path),
+            None => Input::stdin(),
+        };
+        match self.format {
+            Some(format) => input.with_format(format),
+            None => input,
+        }
+    }
+
+    /// The whole input, or each of its lines with `--lines`.
+    fn candidates(&self) -> error::Result<Vec<Vec<u8>>> {
+        if self.lines {
+            self.input().lines(Format::Raw)
+        } else {
+            Ok(vec![self.input().decoded(Format::Raw)?])
+        }
+    }
+
+    /// The base64 alphabet and padding picked by `--url-safe` and `--no-padding`.
+    fn base64(&self) -> base64::Config {
+        match (self.url_safe, self.no_padding) {
+            (false, false) => base64::STANDARD,
+            (false, true) => base64::STANDARD_NO_PAD,
+            (true, false) => base64::URL_SAFE,
+            (true, true) => base64::URL_SAFE_NO_PAD,
+        }
+    }
+
+    fn key(&self) -> Result<&[u8], CliError> {
+        Ok(self.key.as_deref().ok_or("Missing --key or --key-hex")?)
+    }
+
+    /// The `--iv`, which has to be a whole block, if given.
+    fn iv(&self) -> Result<Option<Vec<u8>>, CliError> {
+        match &self.iv {
+            Some(iv) if iv.len() != 16 => {
+                Err(format!("Invalid --iv: expected 16 bytes, got {}", iv.len()).into())
+            }
+            iv => Ok(iv.clone()),
+        }
+    }
+}
+
+fn parse_hex(option: &str, value: &str) -> Result<Vec<u8>, CliError> {
+    encoding::hex_to_bytes(value).map_err(|e| format!("Invalid {option}: {e}").into())
+}
+
+/// What a tool produced: bytes to write out as is, a line of text, or labelled results.
+enum ToolOutput {
+    Bytes(Vec<u8>),
+    Line(String),
+    Fields(Output),
+}
+
+/// Runs a tool with the options it takes, and prints what it produced, or with `--json` an object
+/// with that and how long it took.
+fn tool<F>(name: &str, args: &[String], allowed: &[&str], f: F) -> CliResult
+where
+    F: FnOnce(&ToolOptions) -> Result<ToolOutput, CliError>,
+{
+    let options = ToolOptions::parse(args, allowed)?;
+    let start = Instant::now();
+    let output = f(&options)?;
+    let elapsed = start.elapsed();
+
+    let mut stdout = io::stdout();
+    if options.json {
+        let output = match output {
+            ToolOutput::Bytes(bytes) => Output::new().with("Result", bytes),
+            ToolOutput::Line(line) => Output::new().with("Result", line),
+            ToolOutput::Fields(output) => output,
+        };
+        let json = Json::object([
+            ("tool", Json::string(name)),
+            ("seconds", seconds(elapsed)),
+            ("output", output.to_json()),
+        ]);
+        writeln!(stdout, "{json}")?;
+    } else {
+        match output {
+            ToolOutput::Bytes(bytes) => stdout.write_all(&bytes)?,
+            ToolOutput::Line(line) => writeln!(stdout, "{line}")?,
+            ToolOutput::Fields(output) => write!(stdout, "{output}")?,
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn repeating_xor(args: &[String]) -> CliResult {
+    let allowed = ["--key", "--key-hex", "--input", "--format"];
+    tool("xor", args, &allowed, |options| {
+        let data = options.input().decoded(Format::Raw)?;
+        Ok(ToolOutput::Bytes(xor::repeating_key_xor(
+            &data,
+            options.key()?,
+        )))
+    })
+}
+
+fn codec(format: Format, args: &[String]) -> CliResult {
+    let (direction, args) = args.split_first().ok_or("Missing encode or decode")?;
+    match (format, direction.as_str()) {
+        (Format::Base64, "encode") => {
+            let allowed = ["--url-safe", "--no-padding", "--input"];
+            tool("b64 encode", args, &allowed, |options| {
+                let data = options.input().decoded(Format::Raw)?;
+                Ok(ToolOutput::Line(options.base64().encode(&data)))
+            })
+        }
+        (Format::Base64, "decode") => {
+            let allowed = ["--url-safe", "--no-padding", "--strict", "--input"];
+            tool("b64 decode", args, &allowed, |options| {
+                let data = options.input().decoded(Format::Raw)?;
+                let text = String::from_utf8_lossy(&data);
+                Ok(ToolOutput::Bytes(if options.strict {
+                    options
+                        .base64()
+                        .decode(text.trim_end_matches(['\r', '\n']))?
+                } else {
+                    options.base64().decode_lenient(&text)?
+                }))
+            })
+        }
+        (_, "encode") => tool("hex encode", args, &["--input"], |options| {
+            let data = options.input().decoded(Format::Raw)?;
+            Ok(ToolOutput::Line(encoding::bytes_to_hex(&data)))
+        }),
+        (_, "decode") => tool("hex decode", args, &["--input"], |options| {
+            Ok(ToolOutput::Bytes(options.input().decoded(format)?))
+        }),
+        _ => Err(format!("Expected encode or decode, not {direction}").into()),
+    }
+}
+
+fn break_single_xor(args: &[String]) -> CliResult {
+    let allowed = ["--lines", "--input", "--format"];
+    tool("break-single-xor", args, &allowed, |options| {
+        let candidates = options.candidates()?;
+        let (score, key, plaintext) = attacks::find_single_byte_encryption(&candidates)
+            .ok_or(CliError::Failed("No input to break".to_owned()))?;
+
+        let mut output = Output::new();
+        if options.lines {
+            let line = candidates
+                .iter()
+                .position(|candidate| xor::single_byte_xor(candidate, key) == plaintext)
+                .expect("Plaintext comes from one of the lines");
+            output.push("Line", line + 1);
+        }
+        Ok(ToolOutput::Fields(
+            output
+                .with("Key", vec![key])
+                .with("Score", score)
+                .with("Plaintext", plaintext),
+        ))
+    })
+}
+
+fn break_repeating_xor(args: &[String]) -> CliResult {
+    let allowed = ["--input", "--format"];
+    tool("break-repeating-xor", args, &allowed, |options| {
+        let ciphertext = options.input().decoded(Format::Raw)?;
+        let (key, plaintext) = attacks::decrypt_repeating_key_xor(&ciphertext)
+            .ok_or(CliError::Failed("Input too short to break".to_owned()))?;
+
+        Ok(ToolOutput::Fields(
+            Output::new().with("Key", key).with("Plaintext", plaintext),
+        ))
+    })
+}
+
+fn detect_ecb(args: &[String]) -> CliResult {
+    let allowed = ["--lines", "--input", "--format"];
+    tool("detect-ecb", args, &allowed, |options| {
+        let candidates = options.candidates()?;
+        let detected = attacks::detect_aes_ecb(&candidates)
+            .ok_or(CliError::Failed("No input to look at".to_owned()))?;
+
+        let mut output = Output::new();
+        if options.lines {
+            let line = candidates
+                .iter()
+                .position(|candidate| candidate == detected)
+                .expect("Detected ciphertext is one of the lines");
+            output.push("Line", line + 1);
+        }
+        let repeated = attacks::count_repeated_blocks(detected);
+        Ok(ToolOutput::Fields(
+            output
+                .with("Repeated blocks", repeated)
+                .with("ECB", repeated > 0),
+        ))
+    })
+}
+
+fn aes(args: &[String]) -> CliResult {
+    let [mode, direction, args @ ..] = args else {
+        return Err("Missing mode (ecb or cbc) and direction (encrypt or decrypt)".into());
+    };
+    let allowed: &[&str] = match mode.as_str() {
+        "ecb" => &["--key", "--key-hex", "--no-padding", "--input", "--format"],
+        "cbc" => &[
+            "--key",
+            "--key-hex",
+            "--iv",
+            "--no-padding",
+            "--input",
+            "--format",
+        ],
+        _ => return Err(format!("Unknown mode: {mode}").into()),
+    };
+    let ecb = mode == "ecb";
+
+    let name = format!("aes {mode} {direction}");
+    match direction.as_str() {
+        "encrypt" => tool(&name, args, allowed, |options| {
+            let data = options.input().decoded(Format::Raw)?;
+            let plaintext = if options.no_padding {
+                data
+            } else {
+                padding::pkcs7_pad(&data)
+            };
+            Ok(ToolOutput::Bytes(if ecb {
+                block::encrypt_aes_ecb(&plaintext, options.key()?)?
+            } else {
+                block::encrypt_aes_cbc(&plaintext, options.key()?, options.iv()?)?
+            }))
+        }),
+        "decrypt" => tool(&name, args, allowed, |options| {
+            let data = options.input().decoded(Format::Raw)?;
+            let plaintext = if ecb {
+                block::decrypt_aes_ecb(&data, options.key()?)?
+            } else {
+                block::decrypt_aes_cbc(&data, options.key()?, options.iv()?)?
+            };
+            Ok(ToolOutput::Bytes(if options.no_padding {
+                plaintext
+            } else {
+                padding::unpad(&plaintext)?
+            }))
+        }),
+        _ => Err(format!("Expected encrypt or decrypt, not {direction}").into()),
     }
 }
@@ -0,0 +1,414 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Finds the prime factors of a number below a given bound, by trial division.
+///
+/// Returns each factor found along with its multiplicity, and the remaining cofactor (which is 1
+/// if the number was fully factored).
+pub fn trial_division(n: &BigUint, bound: u64) -> (Vec<(u64, u32)>, BigUint) {
+    let mut factors = vec![];
+    let mut rest = n.clone();
+
+    for d in 2..bound {
+        if rest.is_one() {
+            break;
+        }
+
+        let mut exponent = 0;
+        while (&rest % d).is_zero() {
+            rest /= d;
+            exponent += 1;
+        }
+
+        if exponent > 0 {
+            factors.push((d, exponent));
+        }
+    }
+
+    (factors, rest)
+}
+
+/// Miller-Rabin primality test, with the given number of random witnesses.
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    for p in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47] {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+    if *n < BigUint::from(2u32) {
+        return false;
+    }
+
+    // n - 1 = d * 2^s, with d odd
+    let minus_one = n - 1u32;
+    let s = minus_one.trailing_zeros().unwrap_or(0);
+    let d = &minus_one >> s;
+
+    'witnesses: for _ in 0..rounds {
+        let a = rand::thread_rng().gen_biguint_range(&BigUint::from(2u32), &minus_one);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == minus_one {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == minus_one {
+                continue 'witnesses;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/// Generates a random prime with exactly the given number of bits.
+pub fn random_prime(bits: u64) -> BigUint {
+    loop {
+        let mut n = rand::thread_rng().gen_biguint(bits);
+        n.set_bit(bits - 1, true);
+        n.set_bit(0, true);
+
+        if is_probable_prime(&n, 32) {
+            return n;
+        }
+    }
+}
+
+/// Inverse of `a` modulo `m`, if there is one.
+///
+/// Same as [`BigUint::modinv`], but much faster when `m` fits in a machine word, which matters for
+/// curve arithmetic over small fields.
+pub fn modinv(a: &BigUint, m: &BigUint) -> Option<BigUint> {
+    let Some(m) = m.to_u128() else {
+        return a.modinv(m);
+    };
+    if m == 1 {
+        return Some(BigUint::zero());
+    }
+
+    // extended Euclid, keeping track of the coefficients' absolute values only, since their signs
+    // alternate: none of them is ever bigger than m, so this can't overflow
+    let (mut r0, mut r1) = (m, (a % m).to_u128().expect("Reduced modulo m"));
+    let (mut t0, mut t1) = (0, 1);
+    let mut positive = false;
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 + q * t1);
+        positive = !positive;
+    }
+
+    (r0 == 1).then(|| BigUint::from(if positive { t0 } else { m - t0 }))
+}
+
+/// Chinese remainder theorem: given congruences `x = r (mod m)` as `(r, m)` pairs, finds the
+/// unique `x` modulo the product of all the moduli, returning it along with that product.
+///
+/// Returns `None` if the moduli are not pairwise coprime.
+pub fn crt(congruences: &[(BigUint, BigUint)]) -> Option<(BigUint, BigUint)> {
+    congruences
+        .iter()
+        .try_fold((BigUint::zero(), BigUint::one()), |(x, modulus), (r, m)| {
+            // x + modulus * t = r (mod m)
+            let inverse = (&modulus % m).modinv(m)?;
+            let t = ((r + m - &x % m) * inverse) % m;
+
+            Some((x + &modulus * t, modulus * m))
+        })
+}
+
+/// Finds a square root of `n` modulo an odd prime `p` (with Tonelli-Shanks), if there is one.
+pub fn sqrt_mod(n: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let n = n % p;
+    if n.is_zero() {
+        return Some(n);
+    }
+
+    // Euler's criterion
+    let minus_one = p - 1u32;
+    let half = &minus_one >> 1;
+    if n.modpow(&half, p) != BigUint::one() {
+        return None;
+    }
+
+    // p - 1 = q * 2^s, with q odd
+    let s = minus_one.trailing_zeros().unwrap_or(0);
+    let q = &minus_one >> s;
+
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&half, p) != minus_one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = n.modpow(&q, p);
+    let mut root = n.modpow(&((&q + 1u32) >> 1), p);
+
+    while !t.is_one() {
+        // least i such that t^(2^i) = 1
+        let mut i = 0;
+        let mut t_pow = t.clone();
+        while !t_pow.is_one() {
+            t_pow = &t_pow * &t_pow % p;
+            i += 1;
+        }
+
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        root = root * b % p;
+    }
+
+    Some(root)
+}
+
+/// Cyclic group (written multiplicatively) in which to take discrete logarithms.
+pub trait Group {
+    type Element: Clone + PartialEq;
+
+    fn op(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn pow(&self, a: &Self::Element, exponent: &BigUint) -> Self::Element;
+
+    /// Maps an element to some integer, deterministically, for pseudorandom walks.
+    fn hash(&self, a: &Self::Element) -> u64;
+}
+
+/// Integers modulo a prime, under multiplication.
+pub struct ModP(pub BigUint);
+
+impl Group for ModP {
+    type Element = BigUint;
+
+    fn op(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.0
+    }
+
+    fn pow(&self, a: &BigUint, exponent: &BigUint) -> BigUint {
+        a.modpow(exponent, &self.0)
+    }
+
+    fn hash(&self, a: &BigUint) -> u64 {
+        a.iter_u64_digits().next().unwrap_or(0)
+    }
+}
+
+/// Pohlig-Hellman algorithm: finds the discrete logarithm of `y` to the base `g`, given that the
+/// order of `g` is the product of the given distinct primes, each small enough to brute force.
+///
+/// Returns `None` if `y` is not a power of `g`.
+pub fn pohlig_hellman<G: Group>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    factors: &[u64],
+) -> Option<BigUint> {
+    let order: BigUint = factors.iter().product();
+
+    let mut congruences = vec![];
+    for &r in factors {
+        // in the subgroup of order r, the logarithm is the one we're after modulo r
+        let cofactor = &order / r;
+        let (g_r, y_r) = (group.pow(g, &cofactor), group.pow(y, &cofactor));
+
+        let mut candidate = group.pow(g, &BigUint::zero());
+        let mut residue = 0;
+        while candidate != y_r {
+            if residue == r {
+                return None;
+            }
+            candidate = group.op(&candidate, &g_r);
+            residue += 1;
+        }
+
+        congruences.push((BigUint::from(residue), BigUint::from(r)));
+    }
+
+    crt(&congruences).map(|(x, _)| x)
+}
+
+/// Parameters of the kangaroos' jump function: from an element `y`, they jump by `2^(h(y) mod k)`,
+/// and the tame kangaroo makes `n` jumps before setting the trap.
+#[derive(Clone, Copy, Debug)]
+pub struct JumpParams {
+    pub k: u32,
+    pub n: u64,
+}
+
+impl JumpParams {
+    /// Picks parameters for the given interval width, such that the mean jump is about half its
+    /// square root, and the tame kangaroo goes four times that.
+    pub fn for_width(width: &BigUint) -> Self {
+        let target = (width.sqrt() / 2u32).to_u64().unwrap_or(u64::MAX).max(1);
+
+        let mut k = 1;
+        while k < 63 && ((1u64 << k) - 1) / k as u64 <= target {
+            k += 1;
+        }
+
+        Self {
+            k,
+            n: 4 * ((1u64 << k) - 1) / k as u64,
+        }
+    }
+}
+
+/// Pollard's kangaroo (lambda) algorithm: finds the discrete logarithm of `y` to the base `g`,
+/// knowing it is in the interval `[a, b]`, in about the square root of its width.
+///
+/// Returns `None` if the wild kangaroo doesn't fall into the trap, which means either the
+/// logarithm is not in the interval or we got unlucky (in which case other parameters may help).
+pub fn kangaroo<G: Group>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    (a, b): (&BigUint, &BigUint),
+    params: JumpParams,
+) -> Option<BigUint> {
+    let jumps = (0..params.k)
+        .map(|i| group.pow(g, &(BigUint::one() << i)))
+        .collect::<Vec<_>>();
+    let jump = |element: &G::Element| (group.hash(element) % params.k as u64) as usize;
+
+    // the tame kangaroo starts at g^b and leaves a trap wherever it ends up
+    let mut tame_distance = BigUint::zero();
+    let mut tame = group.pow(g, b);
+    for _ in 0..params.n {
+        let i = jump(&tame);
+        tame_distance += 1u64 << i;
+        tame = group.op(&tame, &jumps[i]);
+    }
+
+    // the wild kangaroo starts at y, and falls into the trap if it ever lands where the tame one
+    // did, since from then on they make the same jumps
+    let bound = b - a + &tame_distance;
+    let mut wild_distance = BigUint::zero();
+    let mut wild = y.clone();
+    while wild_distance <= bound {
+        if wild == tame {
+            return Some(b + tame_distance - wild_distance);
+        }
+
+        let i = jump(&wild);
+        wild_distance += 1u64 << i;
+        wild = group.op(&wild, &jumps[i]);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trial_division_works() {
+        let n = BigUint::from(2u32.pow(3) * 3 * 7u32.pow(2) * 10007);
+
+        assert_eq!(
+            (vec![(2, 3), (3, 1), (7, 2)], BigUint::from(10007u32)),
+            trial_division(&n, 1000)
+        );
+        assert_eq!(
+            (vec![(2, 3), (3, 1), (7, 2), (10007, 1)], BigUint::one()),
+            trial_division(&n, 1 << 16)
+        );
+    }
+
+    #[test]
+    fn is_probable_prime_works() {
+        for n in [2u32, 3, 47, 2311, 65537, 1_000_000_007] {
+            assert!(is_probable_prime(&BigUint::from(n), 16));
+        }
+        // 561 and 41041 are Carmichael numbers
+        for n in [0u64, 1, 4, 561, 41041, 65537 * 65521] {
+            assert!(!is_probable_prime(&BigUint::from(n), 16));
+        }
+
+        let p = random_prime(256);
+        assert_eq!(256, p.bits());
+        assert!(is_probable_prime(&p, 32));
+    }
+
+    #[test]
+    fn pohlig_hellman_works() {
+        // p - 1 = 2 * 3 * 5 * 7 * 11
+        let group = ModP(BigUint::from(2311u32));
+        let g = BigUint::from(3u32);
+        for x in [0u32, 1, 1234, 2309] {
+            let y = group.pow(&g, &BigUint::from(x));
+            let found = pohlig_hellman(&group, &g, &y, &[2, 3, 5, 7, 11]).unwrap();
+
+            assert_eq!(y, group.pow(&g, &found));
+        }
+    }
+
+    #[test]
+    fn kangaroo_works() {
+        let group = ModP(BigUint::from(1_000_000_007u32));
+        let g = BigUint::from(5u32);
+        let (a, b) = (BigUint::from(100_000u32), BigUint::from(300_000u32));
+        let params = JumpParams::for_width(&(&b - &a));
+
+        for x in [100_000u32, 123_456, 299_999] {
+            let y = group.pow(&g, &BigUint::from(x));
+            let found =
+                kangaroo(&group, &g, &y, (&a, &b), params).map(|found| group.pow(&g, &found) == y);
+
+            assert_eq!(Some(true), found);
+        }
+    }
+
+    #[test]
+    fn sqrt_mod_works() {
+        // p = 1 (mod 4), so this goes through the whole Tonelli-Shanks loop
+        let p = BigUint::from(1_000_000_009u32);
+        for n in [0u32, 1, 4, 10, 123_456_789] {
+            let n = BigUint::from(n);
+            match sqrt_mod(&n, &p) {
+                Some(root) => assert_eq!(n, &root * &root % &p),
+                None => assert_eq!(&p - 1u32, n.modpow(&((&p - 1u32) >> 1), &p)),
+            }
+        }
+        assert_eq!(None, sqrt_mod(&BigUint::from(3u32), &BigUint::from(17u32)));
+    }
+
+    #[test]
+    fn modinv_works() {
+        let p = BigUint::parse_bytes(b"233970423115425145524320034830162017933", 10).unwrap();
+        for a in [1u32, 2, 3, 12345, 1_000_000_007] {
+            let a = BigUint::from(a);
+            assert_eq!(a.modinv(&p), modinv(&a, &p));
+            assert_eq!(a.modinv(&(&p << 128)), modinv(&a, &(&p << 128)));
+        }
+        assert_eq!(
+            Some(BigUint::from(5u32)),
+            modinv(&BigUint::from(3u32), &BigUint::from(7u32))
+        );
+        assert_eq!(None, modinv(&BigUint::from(6u32), &BigUint::from(9u32)));
+        assert_eq!(None, modinv(&BigUint::zero(), &p));
+    }
+
+    #[test]
+    fn crt_works() {
+        let congruences =
+            [(2u32, 3u32), (3, 5), (2, 7)].map(|(r, m)| (BigUint::from(r), BigUint::from(m)));
+
+        assert_eq!(
+            Some((BigUint::from(23u32), BigUint::from(105u32))),
+            crt(&congruences)
+        );
+        assert_eq!(
+            None,
+            crt(&[(2u32, 4u32), (1, 6)].map(|(r, m)| (BigUint::from(r), BigUint::from(m))))
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::{block, error, padding};
+
+/// Block cipher mode picked by [`encrypt_with_random_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockMode {
+    Ecb,
+    Cbc,
+}
+
+/// Encrypts under a random key, with 5 to 10 random bytes before and after the plaintext, in
+/// either ECB or CBC mode (with a random IV), chosen at random. Also returns which one it was.
+pub fn encrypt_with_random_mode(plaintext: &[u8]) -> error::Result<(BlockMode, Vec<u8>)> {
+    let key = block::random_aes_key();
+
+    let mut wrapped = vec![];
+    for _ in 0..rand::thread_rng().gen_range(5..=10) {
+        wrapped.push(rand::random());
+    }
+
+    wrapped.extend(plaintext);
+    for _ in 0..rand::thread_rng().gen_range(5..=10) {
+        wrapped.push(rand::random());
+    }
+
+    let wrapped = padding::pkcs7_pad(&wrapped);
+    if rand::random() {
+        Ok((BlockMode::Ecb, block::encrypt_aes_ecb(&wrapped, &key)?))
+    } else {
+        let iv: [u8; 16] = rand::random();
+        Ok((
+            BlockMode::Cbc,
+            block::encrypt_aes_cbc(&wrapped, &key, Some(iv.to_vec()))?,
+        ))
+    }
+}
+
+/// Encrypts the plaintext with ECB under a fixed random key, between a fixed prefix and suffix.
+pub struct EcbOracle {
+    key: Vec<u8>,
+    prefix: Vec<u8>,
+    suffix: Vec<u8>,
+}
+
+impl EcbOracle {
+    pub fn new(prefix: Vec<u8>, suffix: Vec<u8>) -> Self {
+        Self {
+            key: block::random_aes_key(),
+            prefix,
+            suffix,
+        }
+    }
+
+    /// Like [`Self::new`], with a prefix of random bytes, of a random length between 5 and 128.
+    pub fn with_random_prefix(suffix: Vec<u8>) -> Self {
+        let mut prefix = vec![];
+        for _ in 0..rand::thread_rng().gen_range(5..=128) {
+            prefix.push(rand::random());
+        }
+
+        Self::new(prefix, suffix)
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> error::Result<Vec<u8>> {
+        let mut obfuscated = self.prefix.clone();
+        obfuscated.extend(plaintext);
+        obfuscated.extend(&self.suffix);
+
+        block::encrypt_aes_ecb(&padding::pkcs7_pad(&obfuscated), &self.key)
+    }
+}
+
+pub fn parse_cookie(cookie: &[u8]) -> HashMap<String, String> {
+    let mut obj = HashMap::new();
+
+    for (sep, mut kv) in &cookie.iter().group_by(|x| **x == b'&') {
+        if sep {
+            continue;
+        }
+        let mut key = String::new();
+        while let Some(&c) = kv.next() {
+            if c == b'=' {
+                obj.insert(key, kv.copied().map(char::from).collect());
+                break;
+            }
+
+            key.push(char::from(c));
+        }
+    }
+
+    obj
+}
+
+/// Encodes a user profile for the given email, dropping any metacharacters from it.
+pub fn profile_for(email: &[u8]) -> Vec<u8> {
+    let mut result = vec![];
+    result.extend("email=".as_bytes());
+    result.extend(email.iter().filter(|x| **x != b'&' && **x != b'='));
+    result.extend("&uid=10&role=user".as_bytes());
+
+    result
+}
+
+/// Hands out user profiles encrypted with ECB under a fixed random key, and reads them back.
+pub struct ProfileOracle {
+    key: Vec<u8>,
+}
+
+impl ProfileOracle {
+    pub fn new() -> Self {
+        Self {
+            key: block::random_aes_key(),
+        }
+    }
+
+    pub fn encrypt_profile_for(&self, email: &[u8]) -> error::Result<Vec<u8>> {
+        block::encrypt_aes_ecb(&padding::pkcs7_pad(&profile_for(email)), &self.key)
+    }
+
+    pub fn decrypt_profile(&self, ciphertext: &[u8]) -> error::Result<HashMap<String, String>> {
+        let cookie = block::decrypt_aes_ecb(ciphertext, &self.key)?;
+        let cookie = padding::unpad(&cookie)?;
+
+        Ok(parse_cookie(&cookie))
+    }
+}
+
+impl Default for ProfileOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cookie_works() {
+        let result = parse_cookie("foo=bar&baz=qux&zap=zazzle".as_bytes());
+        assert_eq!(3, result.len());
+        assert_eq!(Some("bar".to_owned()), result.get("foo").cloned());
+        assert_eq!(Some("qux".to_owned()), result.get("baz").cloned());
+        assert_eq!(Some("zazzle".to_owned()), result.get("zap").cloned());
+    }
+
+    #[test]
+    fn profile_for_works() {
+        let result: String = profile_for("a&b=c@example.com".as_bytes())
+            .iter()
+            .copied()
+            .map(char::from)
+            .collect();
+        assert_eq!("email=abc@example.com&uid=10&role=user", result);
+    }
+}
@@ -0,0 +1,75 @@
+use itertools::Itertools;
+
+use crate::error::{self, CryptopalsError};
+
+/// Pads `text` up to `target_length`, which must be at most 255 bytes more than its length.
+pub fn pad(text: &[u8], target_length: usize) -> error::Result<Vec<u8>> {
+    let missing = target_length
+        .checked_sub(text.len())
+        .and_then(|missing| u8::try_from(missing).ok())
+        .ok_or(CryptopalsError::LengthMismatch {
+            expected: target_length,
+            actual: text.len(),
+        })?;
+
+    Ok(text
+        .iter()
+        .copied()
+        .chain(std::iter::repeat(missing))
+        .take(target_length)
+        .collect())
+}
+
+/// Pads a message with PKCS#7 up to the next multiple of the block size (always adding padding).
+pub fn pkcs7_pad(message: &[u8]) -> Vec<u8> {
+    pad(message, (message.len() / 16 + 1) * 16).expect("Between 1 and 16 bytes of padding")
+}
+
+/// Strips PKCS#7 padding, which must be there.
+pub fn unpad(text: &[u8]) -> error::Result<Vec<u8>> {
+    if !text.len().is_multiple_of(16) {
+        return Err(CryptopalsError::BadPadding);
+    }
+
+    let padding_length = *text.last().ok_or(CryptopalsError::BadPadding)?;
+    if !(1..=16).contains(&padding_length) || text.len() < padding_length.into() {
+        return Err(CryptopalsError::BadPadding);
+    }
+
+    if text
+        .iter()
+        .rev()
+        .take(padding_length.into())
+        .any(|x| *x != padding_length)
+    {
+        return Err(CryptopalsError::BadPadding);
+    }
+
+    Ok(text
+        .iter()
+        .take(text.len() - (padding_length as usize))
+        .copied()
+        .collect_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_rejects_bad_lengths() {
+        assert!(pad("YELLOW SUBMARINE".as_bytes(), 15).is_err());
+        assert!(pad("YELLOW SUBMARINE".as_bytes(), 16 + 256).is_err());
+    }
+
+    #[test]
+    fn pkcs7_pad_then_unpad_works() {
+        for len in 0..=32 {
+            let message = vec![b'x'; len];
+            let padded = pkcs7_pad(&message);
+
+            assert_eq!(0, padded.len() % 16);
+            assert_eq!(message, unpad(&padded).unwrap());
+        }
+    }
+}
@@ -1,19 +1,44 @@
-use crate::util;
-
-pub fn solve_chall02() {
-    let input = util::read_hex_lines_stdin();
-    let a = input.first().expect("No first operand provided");
-    let b = input.get(1).expect("No second operand provided");
-    let output = xor(a, b);
-    println!("{}", util::bytes_to_hex(&output))
-}
+use crate::{
+    challenge::{Challenge, Output},
+    encoding,
+    error::{self, CryptopalsError},
+    input::{Format, Input},
+    xor,
+};
+
+pub struct Chall02;
+
+impl Challenge for Chall02 {
+    fn number(&self) -> u8 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Fixed XOR"
+    }
 
-pub fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
-    if a.len() != b.len() {
-        panic!("Operators have different length")
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall02(input)
     }
 
-    a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect()
+    fn expected(&self) -> Output {
+        Output::new().with(
+            "XOR",
+            encoding::hex_to_bytes("746865206b696420646f6e277420706c6179").unwrap(),
+        )
+    }
+}
+
+pub fn solve_chall02(input: &Input) -> error::Result<Output> {
+    let input = input.lines(Format::Hex)?;
+    let a = input
+        .first()
+        .ok_or(CryptopalsError::MissingInput("No first operand provided"))?;
+    let b = input
+        .get(1)
+        .ok_or(CryptopalsError::MissingInput("No second operand provided"))?;
+    let output = xor::xor(a, b)?;
+    Ok(Output::new().with("XOR", output))
 }
 
 #[cfg(test)]
@@ -24,10 +49,13 @@ mod tests {
     fn is_correct() {
         assert_eq!(
             "746865206b696420646f6e277420706c6179",
-            util::bytes_to_hex(&xor(
-                &util::hex_to_bytes("1c0111001f010100061a024b53535009181c"),
-                &util::hex_to_bytes("686974207468652062756c6c277320657965")
-            ))
+            encoding::bytes_to_hex(
+                &xor::xor(
+                    &encoding::hex_to_bytes("1c0111001f010100061a024b53535009181c").unwrap(),
+                    &encoding::hex_to_bytes("686974207468652062756c6c277320657965").unwrap()
+                )
+                .unwrap()
+            )
         )
     }
 }
@@ -1,55 +1,64 @@
-use crate::util;
+use crate::{
+    attacks,
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::{Format, Input},
+};
 
-use super::chall02;
+pub struct Chall03;
 
-pub fn solve_chall03() {
-    let lines = util::read_hex_lines_stdin();
-    let input = lines.first().expect("No first operand provided");
-    let (_, key, msg) = find_single_byte_key(input).expect("No single-byte key found");
-    println!("Key: {key:#02x}");
-    println!("{}", util::bytes_to_hex(&msg))
-}
-
-pub fn find_single_byte_key(c: &[u8]) -> Option<(u8, u8, Vec<u8>)> {
-    let mut top_candidate = None;
-
-    for k in u8::MIN..=u8::MAX {
-        let mut augmented_key = vec![];
-        augmented_key.resize(c.len(), k);
+impl Challenge for Chall03 {
+    fn number(&self) -> u8 {
+        3
+    }
 
-        let candidate = chall02::xor(c, &augmented_key);
-        let score = score_english_phrase(&candidate);
+    fn title(&self) -> &'static str {
+        "Single-byte XOR cipher"
+    }
 
-        match top_candidate {
-            Some((top_score, _, _)) if top_score >= score => {} // ignore
-            _ => top_candidate = Some((score, k, candidate)),
-        }
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall03(input)
     }
 
-    top_candidate
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Key", vec![0x58])
+            .with("ASCII", "Cooking MC's like a pound of bacon")
+    }
 }
 
-fn score_english_phrase(phrase: &[u8]) -> u8 {
-    phrase.iter().fold(0, |acc, x| {
-        if x.is_ascii_alphabetic() || *x == b' ' {
-            acc + 1
-        } else {
-            acc
-        }
-    })
+pub fn solve_chall03(input: &Input) -> error::Result<Output> {
+    let lines = input.lines(Format::Hex)?;
+    let input = lines
+        .first()
+        .ok_or(CryptopalsError::MissingInput("No first operand provided"))?;
+    let (score, key, msg) = attacks::find_single_byte_key(input)
+        .ok_or(CryptopalsError::AttackFailed("No single-byte key found"))?;
+    Ok(Output::new()
+        .with("Key", vec![key])
+        .with("Score", score)
+        .with("Message", msg.as_slice())
+        .with(
+            "ASCII",
+            msg.iter().copied().map(char::from).collect::<String>(),
+        ))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::encoding;
+
     use super::*;
 
     #[test]
     fn is_correct() {
-        let (_, key, msg) = find_single_byte_key(&util::hex_to_bytes(
+        let ciphertext = encoding::hex_to_bytes(
             "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736",
-        ))
-        .expect("No single-byte key found");
-        assert_eq!("58", util::bytes_to_hex(&[key]));
+        )
+        .unwrap();
+        let (_, key, msg) =
+            attacks::find_single_byte_key(&ciphertext).expect("No single-byte key found");
+        assert_eq!("58", encoding::bytes_to_hex(&[key]));
         assert_eq!("Cooking MC's like a pound of bacon".as_bytes(), msg);
     }
 }
@@ -1,44 +1,59 @@
-use crate::util;
-
-use super::chall03;
-
-pub fn solve_chall04() {
-    let input = util::read_hex_lines_stdin();
-    let (key, msg) = find_single_byte_encryption(&input).expect("No single-byte encryption found");
-    println!("Key: {key:#02x} ({})", char::from(key));
-    println!("Message: {}", util::bytes_to_hex(&msg));
-    println!(
-        "ASCII: {}",
-        msg.iter().cloned().map(char::from).collect::<String>()
-    );
-}
+use crate::{
+    attacks,
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::{Format, Input},
+};
+
+pub struct Chall04;
+
+impl Challenge for Chall04 {
+    fn number(&self) -> u8 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Detect single-character XOR"
+    }
 
-fn find_single_byte_encryption(seqs: &[Vec<u8>]) -> Option<(u8, Vec<u8>)> {
-    let mut top_candidate = None;
-
-    for seq in seqs {
-        let result = chall03::find_single_byte_key(seq);
-        if let Some((score, _, _)) = result {
-            match top_candidate {
-                Some((top_score, _, _)) if top_score > score => {} // ignore
-                _ => top_candidate = result,
-            }
-        }
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall04(input)
     }
 
-    top_candidate.map(|(_, key, msg)| (key, msg))
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Key", vec![0x35])
+            .with("ASCII", "Now that the party is jumping\n")
+    }
+}
+
+pub fn solve_chall04(input: &Input) -> error::Result<Output> {
+    let input = input.lines(Format::Hex)?;
+    let (score, key, msg) = attacks::find_single_byte_encryption(&input).ok_or(
+        CryptopalsError::AttackFailed("No single-byte encryption found"),
+    )?;
+    Ok(Output::new()
+        .with("Key", vec![key])
+        .with("Score", score)
+        .with("Message", msg.as_slice())
+        .with(
+            "ASCII",
+            msg.iter().copied().map(char::from).collect::<String>(),
+        ))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::encoding;
+
     use super::*;
 
     #[test]
     fn is_correct() {
-        let input = util::read_hex_lines_file("data/4.txt");
-        let (key, msg) =
-            find_single_byte_encryption(&input).expect("No single-byte encryption found");
-        assert_eq!("35", util::bytes_to_hex(&[key]));
+        let input = Input::file("data/4.txt").lines(Format::Hex).unwrap();
+        let (_, key, msg) =
+            attacks::find_single_byte_encryption(&input).expect("No single-byte encryption found");
+        assert_eq!("35", encoding::bytes_to_hex(&[key]));
         assert_eq!("Now that the party is jumping\n".as_bytes(), msg);
     }
 }
@@ -1,19 +1,41 @@
-use std::io;
+use crate::{
+    challenge::{Challenge, Output},
+    encoding, error,
+    input::{Format, Input},
+    xor,
+};
 
-use crate::util;
+pub struct Chall05;
 
-pub fn solve_chall05() {
-    let input = io::read_to_string(io::stdin()).expect("Failed to read input");
-    let output = repeating_key_xor(input.as_bytes(), "ICE".as_bytes());
-    println!("{}", util::bytes_to_hex(&output));
+impl Challenge for Chall05 {
+    fn number(&self) -> u8 {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Implement repeating-key XOR"
+    }
+
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall05(input)
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with(
+            "Ciphertext",
+            encoding::hex_to_bytes(concat!(
+                "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b",
+                "2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"
+            ))
+            .unwrap(),
+        )
+    }
 }
 
-pub fn repeating_key_xor(plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-    plaintext
-        .iter()
-        .zip(key.iter().cycle())
-        .map(|(p, k)| p ^ k)
-        .collect()
+pub fn solve_chall05(input: &Input) -> error::Result<Output> {
+    let plaintext = input.decoded(Format::Raw)?;
+    let output = xor::repeating_key_xor(&plaintext, &input.key_or(b"ICE"));
+    Ok(Output::new().with("Ciphertext", output))
 }
 
 #[cfg(test)]
@@ -24,10 +46,10 @@ mod tests {
     fn is_correct() {
         let input = "Burning 'em, if you ain't quick and nimble
 I go crazy when I hear a cymbal";
-        let output = repeating_key_xor(input.as_bytes(), "ICE".as_bytes());
+        let output = xor::repeating_key_xor(input.as_bytes(), "ICE".as_bytes());
         assert_eq!(
             "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f",
-            util::bytes_to_hex(&output)
+            encoding::bytes_to_hex(&output)
         );
     }
 }
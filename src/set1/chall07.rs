@@ -1,54 +1,60 @@
-use std::io;
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error,
+    input::{Format, Input},
+};
 
-use openssl::symm;
+use super::chall06;
 
-use crate::util;
+pub struct Chall07;
 
-use super::chall06;
+impl Challenge for Chall07 {
+    fn number(&self) -> u8 {
+        7
+    }
 
-pub fn solve_chall07() {
-    let input = io::read_to_string(io::stdin()).expect("Failed to read input");
-    let ciphertext = chall06::base64_decode(&input).expect("Failed to base64-decode");
-    let key = "YELLOW SUBMARINE".as_bytes();
-    let plaintext = decrypt_aes_ecb(&ciphertext, key).expect("Failed to decrypt");
+    fn title(&self) -> &'static str {
+        "AES in ECB mode"
+    }
 
-    println!("Plaintext: {}", util::bytes_to_hex(&plaintext));
-    println!(
-        "ASCII: {}",
-        plaintext
-            .iter()
-            .cloned()
-            .map(char::from)
-            .collect::<String>()
-    );
-}
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall07(input)
+    }
 
-pub fn decrypt_aes_ecb(
-    ciphertext: &[u8],
-    key: &[u8],
-) -> Result<Vec<u8>, openssl::error::ErrorStack> {
-    let cipher = symm::Cipher::aes_128_ecb();
-    let mut decrypter = symm::Crypter::new(cipher, symm::Mode::Decrypt, key, None)?;
-    decrypter.pad(false);
+    fn expected(&self) -> Output {
+        // decrypted as is, padding included
+        let plaintext = [chall06::PLAINTEXT.as_slice(), &[4; 4]].concat();
+        Output::new().with("Plaintext", plaintext)
+    }
+}
 
-    let mut plaintext = vec![0; 2896]; // openssl complains if <2896
-    decrypter.update(ciphertext, &mut plaintext)?;
+pub fn solve_chall07(input: &Input) -> error::Result<Output> {
+    let ciphertext = input.decoded(Format::Base64)?;
+    let key = input.key_or(b"YELLOW SUBMARINE");
+    let plaintext = block::decrypt_aes_ecb(&ciphertext, &key)?;
 
-    plaintext.truncate(ciphertext.len());
-    Ok(plaintext)
+    Ok(Output::new().with("Plaintext", plaintext.as_slice()).with(
+        "ASCII",
+        plaintext
+            .iter()
+            .copied()
+            .map(char::from)
+            .collect::<String>(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::util;
+
     use super::*;
 
     #[test]
     fn is_correct() {
-        let reader = util::get_file_reader("data/7.txt");
-        let input = io::read_to_string(reader).expect("Failed to read file");
-        let ciphertext = chall06::base64_decode(&input).expect("Failed to base64-decode");
+        let ciphertext = Input::file("data/7.txt").decoded(Format::Base64).unwrap();
         let key = "YELLOW SUBMARINE".as_bytes();
-        let plaintext = decrypt_aes_ecb(&ciphertext, key).expect("Failed to decrypt");
+        let plaintext = block::decrypt_aes_ecb(&ciphertext, key).expect("Failed to decrypt");
 
         // message is too long to check the entire thing here; this is a heuristic
         let msg_needle = "Play that funky music".as_bytes();
@@ -1,8 +0,0 @@
-pub mod chall01;
-pub mod chall02;
-pub mod chall03;
-pub mod chall04;
-pub mod chall05;
-pub mod chall06;
-pub mod chall07;
-pub mod chall08;
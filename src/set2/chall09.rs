@@ -1,25 +1,37 @@
-use crate::util;
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::{Format, Input},
+    padding,
+};
 
-pub fn solve_chall09() {
-    let input = util::read_one_line();
-    let output = pad(input.as_bytes(), 20);
-    println!("{}", String::from_utf8(output).expect("Invalid UTF-8"))
-}
+pub struct Chall09;
+
+impl Challenge for Chall09 {
+    fn number(&self) -> u8 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Implement PKCS#7 padding"
+    }
 
-pub fn pad(text: &[u8], target_length: usize) -> Vec<u8> {
-    if text.len() > target_length {
-        panic!("Text too long for {target_length}!");
-    };
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall09(input)
+    }
 
-    let missing = (target_length - text.len())
-        .try_into()
-        .expect("Difference cannot be represented!");
+    fn expected(&self) -> Output {
+        Output::new().with("Padded", "YELLOW SUBMARINE\x04\x04\x04\x04")
+    }
+}
 
-    text.iter()
-        .copied()
-        .chain(std::iter::repeat(missing))
-        .take(target_length)
-        .collect()
+pub fn solve_chall09(input: &Input) -> error::Result<Output> {
+    let lines = input.lines(Format::Raw)?;
+    let text = lines
+        .first()
+        .ok_or(CryptopalsError::MissingInput("No text provided"))?;
+    let output = padding::pad(text, 20)?;
+    Ok(Output::new().with("Padded", String::from_utf8_lossy(&output).into_owned()))
 }
 
 #[cfg(test)]
@@ -30,7 +42,7 @@ mod tests {
     fn is_correct() {
         assert_eq!(
             "YELLOW SUBMARINE\x04\x04\x04\x04".as_bytes(),
-            pad("YELLOW SUBMARINE".as_bytes(), 20)
+            padding::pad("YELLOW SUBMARINE".as_bytes(), 20).unwrap()
         )
     }
 }
@@ -1,59 +1,62 @@
-use itertools::Itertools;
-use rand::{self, Rng};
+use std::cell::Cell;
 
-use super::chall10;
+use crate::{
+    attacks,
+    challenge::{Challenge, Output},
+    error,
+    input::Input,
+    oracles::{self, BlockMode},
+};
 
-pub fn solve_chall11() {
-    if aes_ecb_detector(&aes_encryption_oracle).expect("Failed to detect") {
-        println!("Mode: ECB");
-    } else {
-        println!("Mode: CBC");
-    }
-}
-
-pub fn random_aes_key() -> Vec<u8> {
-    let bytes: [u8; 16] = rand::random();
-
-    bytes.to_vec()
-}
+pub struct Chall11;
 
-static mut ORACLE_CHOICE: bool = false;
-
-fn aes_encryption_oracle(plaintext: &[u8]) -> chall10::OpenSSLResult<Vec<u8>> {
-    let key = random_aes_key();
+impl Challenge for Chall11 {
+    fn number(&self) -> u8 {
+        11
+    }
 
-    let mut wrapped = vec![];
-    for _ in 0..rand::thread_rng().gen_range(5..=10) {
-        wrapped.push(rand::random());
+    fn title(&self) -> &'static str {
+        "An ECB/CBC detection oracle"
     }
 
-    wrapped.extend(plaintext);
-    for _ in 0..rand::thread_rng().gen_range(5..=10) {
-        wrapped.push(rand::random());
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall11()
     }
 
-    if rand::random() {
-        println!("Oracle: ECB");
-        unsafe {
-            ORACLE_CHOICE = true;
-        }
-        chall10::encrypt_aes_ecb(&wrapped, &key)
-    } else {
-        println!("Oracle: CBC");
-        let iv: [u8; 16] = rand::random();
-        chall10::encrypt_aes_cbc(&wrapped, &key, Some(iv.to_vec()))
+    fn expected(&self) -> Output {
+        Output::new().with("Matches oracle", true)
     }
 }
 
-pub fn aes_ecb_detector<F>(f: &F) -> chall10::OpenSSLResult<bool>
-where
-    F: Fn(&[u8]) -> chall10::OpenSSLResult<Vec<u8>>,
-{
-    let plaintext = [0; 64];
-    let ciphertext = f(&plaintext)?;
+pub fn solve_chall11() -> error::Result<Output> {
+    let queries = Cell::new(0_usize);
+    let choice = Cell::new(None);
+    let ecb = attacks::aes_ecb_detector(&|plaintext: &[u8]| {
+        queries.set(queries.get() + 1);
+        aes_encryption_oracle(plaintext, &choice)
+    })?;
+    Ok(Output::new()
+        .with("Mode", if ecb { "ECB" } else { "CBC" })
+        .with(
+            "Matches oracle",
+            ecb == (choice.get() == Some(BlockMode::Ecb)),
+        )
+        .with("Queries", queries.get()))
+}
+
+/// Encrypts with a random mode, recording the mode it picked in `choice`.
+fn aes_encryption_oracle(
+    plaintext: &[u8],
+    choice: &Cell<Option<BlockMode>>,
+) -> error::Result<Vec<u8>> {
+    let (mode, ciphertext) = oracles::encrypt_with_random_mode(plaintext)?;
+    match mode {
+        BlockMode::Ecb => eprintln!("Oracle: ECB"),
+        BlockMode::Cbc => eprintln!("Oracle: CBC"),
+    }
+    choice.set(Some(mode));
 
-    // 2 equal consecutive blocks
-    Ok(ciphertext.chunks(16).tuple_windows().any(|(x, y)| x == y))
+    Ok(ciphertext)
 }
 
 #[cfg(test)]
@@ -62,9 +65,12 @@ mod tests {
 
     #[test]
     fn is_correct() {
-        let result = aes_ecb_detector(&aes_encryption_oracle).expect("Failed to detect");
-        let expected = unsafe { ORACLE_CHOICE };
+        let choice = Cell::new(None);
+        let result = attacks::aes_ecb_detector(&|plaintext: &[u8]| {
+            aes_encryption_oracle(plaintext, &choice)
+        })
+        .expect("Failed to detect");
 
-        assert_eq!(expected, result);
+        assert_eq!(choice.get() == Some(BlockMode::Ecb), result);
     }
 }
@@ -1,86 +1,56 @@
-use std::collections::HashMap;
+use std::{cell::Cell, sync::OnceLock};
 
-use itertools::Itertools;
+use crate::{
+    attacks,
+    challenge::{Challenge, Output},
+    error,
+    input::Input,
+    oracles::ProfileOracle,
+};
 
-use crate::set1::chall07;
+pub struct Chall13;
 
-use super::{chall10, chall11, chall15};
-
-pub fn solve_chall13() {
-    let result = make_admin_profile(encrypt_profile_for, decrypt_profile).unwrap();
-    println!("{result:?}")
-}
-
-fn parse_cookie(cookie: &[u8]) -> HashMap<String, String> {
-    let mut obj = HashMap::new();
-
-    for (sep, mut kv) in &cookie.iter().group_by(|x| **x == b'&') {
-        if sep {
-            continue;
-        }
-        let mut key = String::new();
-        while let Some(&c) = kv.next() {
-            if c == b'=' {
-                obj.insert(key, kv.copied().map(char::from).collect());
-                break;
-            }
-
-            key.push(char::from(c));
-        }
+impl Challenge for Chall13 {
+    fn number(&self) -> u8 {
+        13
     }
 
-    obj
-}
-
-fn profile_for(email: &[u8]) -> Vec<u8> {
-    let mut result = vec![];
-    result.extend("email=".as_bytes());
-    result.extend(email.iter().filter(|x| **x != b'&' && **x != b'='));
-    result.extend("&uid=10&role=user".as_bytes());
-
-    result
-}
-
-static mut ORACLE_KEY: Option<Vec<u8>> = None;
+    fn title(&self) -> &'static str {
+        "ECB cut-and-paste"
+    }
 
-fn get_oracle_key() -> &'static Vec<u8> {
-    unsafe {
-        if ORACLE_KEY.is_none() {
-            ORACLE_KEY = Some(chall11::random_aes_key());
-        }
-        ORACLE_KEY.as_ref().unwrap()
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall13()
     }
-}
 
-fn encrypt_profile_for(email: &[u8]) -> chall10::OpenSSLResult<Vec<u8>> {
-    let profile = profile_for(email);
-    chall10::encrypt_aes_ecb(&profile, get_oracle_key())
+    fn expected(&self) -> Output {
+        Output::new().with("Role", "admin")
+    }
 }
 
-fn decrypt_profile(ciphertext: &[u8]) -> chall10::OpenSSLResult<HashMap<String, String>> {
-    let cookie = chall07::decrypt_aes_ecb(ciphertext, get_oracle_key())?;
-    let cookie = chall15::unpad(&cookie).unwrap();
-
-    Ok(parse_cookie(&cookie))
+pub fn solve_chall13() -> error::Result<Output> {
+    let oracle = get_oracle();
+    let queries = Cell::new(0_usize);
+    let result = attacks::make_admin_profile(
+        |email| {
+            queries.set(queries.get() + 1);
+            oracle.encrypt_profile_for(email)
+        },
+        |ciphertext| {
+            queries.set(queries.get() + 1);
+            oracle.decrypt_profile(ciphertext)
+        },
+    )?;
+    Ok(Output::new()
+        .with("Profile", format!("{result:?}"))
+        .with("Role", result.get("role").cloned().unwrap_or_default())
+        .with("Queries", queries.get()))
 }
 
-fn make_admin_profile<E, D>(
-    encrypter: E,
-    decrypter: D,
-) -> chall10::OpenSSLResult<HashMap<String, String>>
-where
-    E: Fn(&[u8]) -> chall10::OpenSSLResult<Vec<u8>>,
-    D: Fn(&[u8]) -> chall10::OpenSSLResult<HashMap<String, String>>,
-{
-    // ..........."d=10&role=" || "admin"...padding
-    let payload = "xxxxxxxxxxadmin\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0bxxx";
+static ORACLE: OnceLock<ProfileOracle> = OnceLock::new();
 
-    let mut ciphertext = encrypter(payload.as_bytes())?;
-    ciphertext.copy_within(16..(2 * 16), 3 * 16);
-    ciphertext.copy_within((2 * 16).., 16);
-    ciphertext.truncate(ciphertext.len().saturating_sub(16));
-
-    decrypter(&ciphertext)
+fn get_oracle() -> &'static ProfileOracle {
+    ORACLE.get_or_init(ProfileOracle::new)
 }
 
 #[cfg(test)]
@@ -89,35 +59,12 @@ mod tests {
 
     #[test]
     fn is_correct() {
-        let result = make_admin_profile(encrypt_profile_for, decrypt_profile).unwrap();
-        assert_eq!(Some(&"admin".to_owned()), result.get(&"role".to_owned()));
-    }
-
-    #[test]
-    fn parse_cookie_works() {
-        let result = parse_cookie("foo=bar&baz=qux&zap=zazzle".as_bytes());
-        assert_eq!(3, result.len());
-        assert_eq!(
-            Some("bar".to_owned()),
-            result.get(&"foo".to_owned()).cloned()
-        );
-        assert_eq!(
-            Some("qux".to_owned()),
-            result.get(&"baz".to_owned()).cloned()
-        );
-        assert_eq!(
-            Some("zazzle".to_owned()),
-            result.get(&"zap".to_owned()).cloned()
-        );
-    }
-
-    #[test]
-    fn profile_for_works() {
-        let result: String = profile_for("a&b=c@example.com".as_bytes())
-            .iter()
-            .copied()
-            .map(char::from)
-            .collect();
-        assert_eq!("email=abc@example.com&uid=10&role=user", result);
+        let oracle = get_oracle();
+        let result = attacks::make_admin_profile(
+            |email| oracle.encrypt_profile_for(email),
+            |ciphertext| oracle.decrypt_profile(ciphertext),
+        )
+        .unwrap();
+        assert_eq!(Some(&"admin".to_owned()), result.get("role"));
     }
 }
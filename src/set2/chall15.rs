@@ -1,54 +1,56 @@
-use itertools::Itertools;
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::{Format, Input},
+    padding,
+};
 
-use crate::util;
+pub struct Chall15;
 
-pub fn solve_chall15() {
-    let input = util::read_one_line();
-    let output = unpad(input.as_bytes()).expect("Invalid padding");
-    println!("{}", String::from_utf8(output).expect("Invalid UTF-8"))
-}
+impl Challenge for Chall15 {
+    fn number(&self) -> u8 {
+        15
+    }
 
-// padding is required!!
-pub fn unpad(text: &[u8]) -> Result<Vec<u8>, ()> {
-    if text.len() % 16 != 0 {
-        return Err(());
+    fn title(&self) -> &'static str {
+        "PKCS#7 padding validation"
     }
 
-    let padding_length = *text.last().ok_or(())?;
-    if !(1..=16).contains(&padding_length) || text.len() < padding_length.into() {
-        return Err(());
+    fn solve(&self, input: &Input) -> error::Result<Output> {
+        solve_chall15(input)
     }
 
-    if text
-        .iter()
-        .rev()
-        .take(padding_length.into())
-        .any(|x| *x != padding_length)
-    {
-        return Err(());
+    fn expected(&self) -> Output {
+        Output::new().with("Unpadded", "ICE ICE BABY")
     }
+}
 
-    Ok(text
-        .iter()
-        .take(text.len() - (padding_length as usize))
-        .copied()
-        .collect_vec())
+pub fn solve_chall15(input: &Input) -> error::Result<Output> {
+    let lines = input.lines(Format::Raw)?;
+    let text = lines
+        .first()
+        .ok_or(CryptopalsError::MissingInput("No text provided"))?;
+    let output = padding::unpad(text)?;
+    Ok(Output::new().with("Unpadded", String::from_utf8_lossy(&output).into_owned()))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::CryptopalsError;
+
     use super::*;
 
     #[test]
     fn is_correct() {
         assert_eq!(
             "ICE ICE BABY",
-            String::from_utf8(unpad("ICE ICE BABY\x04\x04\x04\x04".as_bytes()).unwrap()).unwrap()
+            String::from_utf8(padding::unpad("ICE ICE BABY\x04\x04\x04\x04".as_bytes()).unwrap())
+                .unwrap()
         );
         assert_eq!(
             "YELLOW SUBMARINE",
             String::from_utf8(
-                unpad(
+                padding::unpad(
                     "YELLOW SUBMARINE\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10"
                         .as_bytes()
                 )
@@ -60,7 +62,13 @@ mod tests {
 
     #[test]
     fn detect_invalid() {
-        assert_eq!(Err(()), unpad("ICE ICE BABY\x05\x05\x05\x05".as_bytes()));
-        assert_eq!(Err(()), unpad("ICE ICE BABY\x01\x02\x03\x04".as_bytes()));
+        assert!(matches!(
+            padding::unpad("ICE ICE BABY\x05\x05\x05\x05".as_bytes()),
+            Err(CryptopalsError::BadPadding)
+        ));
+        assert!(matches!(
+            padding::unpad("ICE ICE BABY\x01\x02\x03\x04".as_bytes()),
+            Err(CryptopalsError::BadPadding)
+        ));
     }
 }
@@ -1,7 +0,0 @@
-pub mod chall09;
-pub mod chall10;
-pub mod chall11;
-pub mod chall12;
-pub mod chall13;
-pub mod chall14;
-pub mod chall15;
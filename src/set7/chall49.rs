@@ -0,0 +1,283 @@
+use std::sync::OnceLock;
+
+use itertools::Itertools;
+
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    oracles, padding, xor,
+};
+
+pub struct Chall49;
+
+impl Challenge for Chall49 {
+    fn number(&self) -> u8 {
+        49
+    }
+
+    fn title(&self) -> &'static str {
+        "CBC-MAC Message Forgery"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall49()
+    }
+
+    fn expected(&self) -> Output {
+        let forged = Transfer {
+            from: VICTIM_ID,
+            to: ATTACKER_ID,
+            amount: 1_000_000,
+        };
+        Output::new()
+            .with(
+                "Forged (attacker-controlled IV)",
+                format!("{:?}", Some(forged)),
+            )
+            .with("Forged transfer accepted (fixed IV)", true)
+    }
+}
+
+pub fn solve_chall49() -> error::Result<Output> {
+    let transfer = forge_transfer_from(client_sign_transfer, server_process_transfer, VICTIM_ID)?;
+
+    let transfers = forge_tx_list_extension(
+        capture_victim_tx_list,
+        client_sign_tx_list,
+        server_process_tx_list,
+    )?;
+    let accepted = transfers.contains(&Transfer {
+        from: VICTIM_ID,
+        to: ATTACKER_ID,
+        amount: 1_000_000,
+    });
+
+    Ok(Output::new()
+        .with("Forged (attacker-controlled IV)", format!("{transfer:?}"))
+        .with("Forged (fixed IV)", format!("{transfers:?}"))
+        .with("Forged transfer accepted (fixed IV)", accepted))
+}
+
+const VICTIM_ID: u32 = 1;
+const ATTACKER_ID: u32 = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: u32,
+    pub to: u32,
+    pub amount: u64,
+}
+
+static ORACLE_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn get_oracle_key() -> &'static Vec<u8> {
+    ORACLE_KEY.get_or_init(block::random_aes_key)
+}
+
+/// Web client, logged in as the attacker: it only signs transfers from the attacker's account.
+/// Returns `message || IV || MAC`.
+fn client_sign_transfer(to: u32, amount: u64) -> error::Result<Vec<u8>> {
+    let message = format!("from={ATTACKER_ID}&to={to}&amount={amount}");
+    let iv = block::random_aes_key();
+    let mac = block::cbc_mac(message.as_bytes(), get_oracle_key(), Some(iv.clone()))?;
+
+    let mut request = message.into_bytes();
+    request.extend(iv);
+    request.extend(mac);
+
+    Ok(request)
+}
+
+/// API server: verifies `message || IV || MAC` and returns the transfer it would perform.
+fn server_process_transfer(request: &[u8]) -> error::Result<Option<Transfer>> {
+    if request.len() < 2 * 16 {
+        return Ok(None);
+    }
+
+    let (message, rest) = request.split_at(request.len() - 2 * 16);
+    let (iv, mac) = rest.split_at(16);
+    if block::cbc_mac(message, get_oracle_key(), Some(iv.to_vec()))? != mac {
+        return Ok(None);
+    }
+
+    let params = oracles::parse_cookie(message);
+    let from = params.get("from").and_then(|v| v.parse().ok());
+    let to = params.get("to").and_then(|v| v.parse().ok());
+    let amount = params.get("amount").and_then(|v| v.parse().ok());
+
+    Ok(match (from, to, amount) {
+        (Some(from), Some(to), Some(amount)) => Some(Transfer { from, to, amount }),
+        _ => None,
+    })
+}
+
+/// Forges a request moving money from the victim to the attacker, by signing a request from the
+/// attacker to themselves and then flipping the first block through the (attacker-controlled) IV.
+fn forge_transfer_from<S, P>(sign: S, process: P, victim: u32) -> error::Result<Option<Transfer>>
+where
+    S: Fn(u32, u64) -> error::Result<Vec<u8>>,
+    P: Fn(&[u8]) -> error::Result<Option<Transfer>>,
+{
+    let mut request = sign(ATTACKER_ID, 1_000_000)?;
+
+    let original = format!("from={ATTACKER_ID}");
+    let forged = format!("from={victim}");
+    if original.len() != forged.len() {
+        return Err(CryptopalsError::Unsupported(
+            "Only account IDs of the same length are supported",
+        ));
+    }
+
+    // "from=#" is always within the first block, so only it (and the IV) needs to change
+    let diff = xor::xor(original.as_bytes(), forged.as_bytes())?;
+    let iv_start = request.len() - 2 * 16;
+    for (i, d) in diff.iter().enumerate() {
+        request[i] ^= d;
+        request[iv_start + i] ^= d;
+    }
+
+    process(&request)
+}
+
+/// Victim's web client, sending a legitimate multi-transaction request (`message || MAC`),
+/// which the attacker captures. Each call captures a different payment.
+fn capture_victim_tx_list() -> error::Result<Vec<u8>> {
+    let amount: u16 = rand::random();
+    let message = format!("from={VICTIM_ID}&tx_list=3:100;4:{amount}");
+    let mac = block::cbc_mac(message.as_bytes(), get_oracle_key(), None)?;
+
+    let mut request = message.into_bytes();
+    request.extend(mac);
+
+    Ok(request)
+}
+
+/// Web client, logged in as the attacker: it only signs transaction lists from the attacker's
+/// account. Returns `message || MAC`.
+fn client_sign_tx_list(transactions: &[(u32, u64)]) -> error::Result<Vec<u8>> {
+    let tx_list = transactions
+        .iter()
+        .map(|(to, amount)| format!("{to}:{amount}"))
+        .join(";");
+    let message = format!("from={ATTACKER_ID}&tx_list={tx_list}");
+    let mac = block::cbc_mac(message.as_bytes(), get_oracle_key(), None)?;
+
+    let mut request = message.into_bytes();
+    request.extend(mac);
+
+    Ok(request)
+}
+
+/// API server: verifies `message || MAC` (with a fixed IV) and returns the transfers it would
+/// perform. Malformed transactions are skipped.
+fn server_process_tx_list(request: &[u8]) -> error::Result<Vec<Transfer>> {
+    if request.len() < 16 {
+        return Ok(vec![]);
+    }
+
+    let (message, mac) = request.split_at(request.len() - 16);
+    if block::cbc_mac(message, get_oracle_key(), None)? != mac {
+        return Ok(vec![]);
+    }
+
+    let params = oracles::parse_cookie(message);
+    let Some(from) = params.get("from").and_then(|v| v.parse().ok()) else {
+        return Ok(vec![]);
+    };
+    let Some(tx_list) = params.get("tx_list") else {
+        return Ok(vec![]);
+    };
+
+    Ok(tx_list
+        .split(';')
+        .filter_map(|tx| {
+            let (to, amount) = tx.split_once(':')?;
+            Some(Transfer {
+                from,
+                to: to.parse().ok()?,
+                amount: amount.parse().ok()?,
+            })
+        })
+        .collect())
+}
+
+/// Forges a request from the victim that also pays the attacker, by length-extending a captured
+/// request with one signed by the attacker, gluing them together with the captured MAC.
+fn forge_tx_list_extension<C, S, P>(capture: C, sign: S, process: P) -> error::Result<Vec<Transfer>>
+where
+    C: Fn() -> error::Result<Vec<u8>>,
+    S: Fn(&[(u32, u64)]) -> error::Result<Vec<u8>>,
+    P: Fn(&[u8]) -> error::Result<Vec<Transfer>>,
+{
+    let own = sign(&[(ATTACKER_ID, 1), (ATTACKER_ID, 1_000_000)])?;
+    let (own_message, own_mac) = own.split_at(own.len() - 16);
+
+    loop {
+        let captured = capture()?;
+        let (victim_message, victim_mac) = captured.split_at(captured.len() - 16);
+
+        // our first block becomes garbage, which would break parsing if it contained a '&',
+        // so wait for another captured request in that case
+        let glue = xor::xor(&own_message[..16], victim_mac)?;
+        if glue.contains(&b'&') {
+            continue;
+        }
+
+        let mut forged = padding::pkcs7_pad(victim_message);
+        forged.extend(glue);
+        forged.extend(&own_message[16..]);
+        forged.extend(own_mac);
+
+        return process(&forged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let transfer =
+            forge_transfer_from(client_sign_transfer, server_process_transfer, VICTIM_ID).unwrap();
+        assert_eq!(
+            Some(Transfer {
+                from: VICTIM_ID,
+                to: ATTACKER_ID,
+                amount: 1_000_000
+            }),
+            transfer
+        );
+
+        let transfers = forge_tx_list_extension(
+            capture_victim_tx_list,
+            client_sign_tx_list,
+            server_process_tx_list,
+        )
+        .unwrap();
+        assert!(transfers.iter().all(|t| t.from == VICTIM_ID));
+        assert!(transfers.contains(&Transfer {
+            from: VICTIM_ID,
+            to: ATTACKER_ID,
+            amount: 1_000_000
+        }));
+    }
+
+    #[test]
+    fn server_rejects_tampering() {
+        let mut request = client_sign_transfer(3, 10).unwrap();
+        assert_eq!(
+            Some(Transfer {
+                from: ATTACKER_ID,
+                to: 3,
+                amount: 10
+            }),
+            server_process_transfer(&request).unwrap()
+        );
+
+        request[5] ^= 1;
+        assert_eq!(None, server_process_transfer(&request).unwrap());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    encoding,
+    error::{self, CryptopalsError},
+    input::Input,
+    padding, xor,
+};
+
+pub struct Chall50;
+
+impl Challenge for Chall50 {
+    fn number(&self) -> u8 {
+        50
+    }
+
+    fn title(&self) -> &'static str {
+        "Hashing with CBC-MAC"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall50()
+    }
+
+    fn expected(&self) -> Output {
+        let hash = encoding::hex_to_bytes("296b8d7cb78a243dda4d0a61d33bbdd1").unwrap();
+        Output::new()
+            .with("Hash", hash.as_slice())
+            .with("Forged hash", hash)
+    }
+}
+
+pub fn solve_chall50() -> error::Result<Output> {
+    let original = "alert('MZA who was that?');\n".as_bytes();
+    let payload = "alert('Ayo, the Wu is back!');".as_bytes();
+    let forged = forge_snippet(original, payload)?;
+
+    Ok(Output::new()
+        .with("Hash", hash(original)?)
+        .with("Forged", forged.as_slice())
+        .with("Forged hash", hash(&forged)?))
+}
+
+const HASH_KEY: &[u8] = "YELLOW SUBMARINE".as_bytes();
+
+/// CBC-MAC under a fixed key and IV, (mis)used as a hash function.
+pub fn hash(message: &[u8]) -> error::Result<Vec<u8>> {
+    block::cbc_mac(message, HASH_KEY, None)
+}
+
+/// Builds a snippet that starts with the given payload (commenting out the rest of the line) and
+/// hashes to the same value as the original one.
+fn forge_snippet(original: &[u8], payload: &[u8]) -> error::Result<Vec<u8>> {
+    if original.len() < 16 {
+        return Err(CryptopalsError::Unsupported(
+            "Only snippets with at least one full block are supported",
+        ));
+    }
+
+    let mut payload = payload.to_vec();
+
+    loop {
+        let mut commented = payload.clone();
+        commented.extend("//".as_bytes());
+
+        // the hash of the (padded) payload is the CBC state right after it, so XORing it into the
+        // original's first block puts us back on the original's chain
+        let state = hash(&commented)?;
+        let glue = xor::xor(&original[..16], &state)?;
+
+        let mut forged = padding::pkcs7_pad(&commented);
+        forged.extend(glue);
+        if forged[commented.len()..]
+            .iter()
+            .any(|b| [b'\n', b'\r'].contains(b))
+        {
+            // the padding (10 or 13 bytes of it) or the glue block would end the comment; try again
+            // with a different payload
+            payload.push(b' ');
+            continue;
+        }
+        forged.extend(&original[16..]);
+
+        return Ok(forged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let original = "alert('MZA who was that?');\n".as_bytes();
+        assert_eq!(
+            "296b8d7cb78a243dda4d0a61d33bbdd1",
+            encoding::bytes_to_hex(&hash(original).unwrap())
+        );
+
+        let payload = "alert('Ayo, the Wu is back!');".as_bytes();
+        let forged = forge_snippet(original, payload).unwrap();
+
+        assert_ne!(original, forged);
+        assert_eq!(hash(original).unwrap(), hash(&forged).unwrap());
+        assert!(forged.starts_with(payload));
+
+        // the payload (plus any extra spaces and the comment marker) is properly padded
+        let prefix_len = forged.len() - original.len();
+        let prefix = padding::unpad(&forged[..prefix_len]).unwrap();
+        assert!(prefix.ends_with("//".as_bytes()));
+        // and the comment only ends at the original's trailing newline
+        assert_eq!(
+            Some(forged.len() - 1),
+            forged.iter().position(|b| [b'\n', b'\r'].contains(b))
+        );
+    }
+
+    #[test]
+    fn padding_does_not_end_the_comment() {
+        let original = "alert('MZA who was that?');\n".as_bytes();
+        // "//" makes these 6 and 3 bytes long, which would pad them with '\n' and '\r'
+        for payload in ["f();", "f"] {
+            let forged = forge_snippet(original, payload.as_bytes()).unwrap();
+
+            assert_eq!(hash(original).unwrap(), hash(&forged).unwrap());
+            assert_eq!(
+                Some(forged.len() - 1),
+                forged.iter().position(|b| [b'\n', b'\r'].contains(b))
+            );
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::{cmp::Ordering, io::Write};
+
+use flate2::{write::DeflateEncoder, Compression};
+use itertools::Itertools;
+
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    padding,
+};
+
+pub struct Chall51;
+
+impl Challenge for Chall51 {
+    fn number(&self) -> u8 {
+        51
+    }
+
+    fn title(&self) -> &'static str {
+        "Compression Ratio Side-Channel Attacks"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall51()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Session ID (CTR)", SESSION_ID)
+            .with("Session ID (CBC)", SESSION_ID)
+    }
+}
+
+pub fn solve_chall51() -> error::Result<Output> {
+    Ok(Output::new()
+        .with(
+            "Session ID (CTR)",
+            recover_session_id(ctr_compression_oracle)?,
+        )
+        .with(
+            "Session ID (CBC)",
+            recover_session_id(cbc_compression_oracle)?,
+        ))
+}
+
+const SESSION_ID: &str = "TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+
+fn format_request(body: &[u8]) -> Vec<u8> {
+    let mut request = format!(
+        concat!(
+            "POST / HTTP/1.1\n",
+            "Host: hapless.com\n",
+            "Cookie: sessionid={}\n",
+            "Content-Length: {}\n"
+        ),
+        SESSION_ID,
+        body.len()
+    )
+    .into_bytes();
+    request.extend(body);
+
+    request
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+    encoder.write_all(data).expect("Failed to compress");
+    encoder.finish().expect("Failed to compress")
+}
+
+/// Encrypts (or decrypts) with AES in CTR mode: a 64-bit little-endian nonce followed by a 64-bit
+/// little-endian block counter make up the keystream input.
+pub fn aes_ctr(text: &[u8], key: &[u8], nonce: u64) -> error::Result<Vec<u8>> {
+    let mut result = vec![];
+
+    for (counter, chunk) in text.chunks(16).enumerate() {
+        let mut input = nonce.to_le_bytes().to_vec();
+        input.extend((counter as u64).to_le_bytes());
+        let keystream = block::encrypt_aes_ecb(&input, key)?;
+
+        result.extend(chunk.iter().zip(keystream).map(|(x, k)| x ^ k));
+    }
+
+    Ok(result)
+}
+
+fn ctr_compression_oracle(body: &[u8]) -> error::Result<usize> {
+    let compressed = compress(&format_request(body));
+    let ciphertext = aes_ctr(&compressed, &block::random_aes_key(), rand::random())?;
+
+    Ok(ciphertext.len())
+}
+
+fn cbc_compression_oracle(body: &[u8]) -> error::Result<usize> {
+    let compressed = compress(&format_request(body));
+    let iv = block::random_aes_key();
+    let ciphertext = block::encrypt_aes_cbc(
+        &padding::pkcs7_pad(&compressed),
+        &block::random_aes_key(),
+        Some(iv),
+    )?;
+
+    Ok(ciphertext.len())
+}
+
+const BASE64_ALPHABET: &[u8] =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=".as_bytes();
+
+/// Printable bytes that never appear in the request nor in the session ID.
+const FILLER: &[u8] = "!@#$%^&*()[]{}<>~|`;,_'?".as_bytes();
+
+/// How much of the known text goes right before each guess: keeping it constant means the right
+/// guess always extends a match of the same length, so Huffman coding doesn't get in the way.
+const WINDOW: usize = 15;
+
+/// Incompressible fillers of different kinds, which grow the compressed output at different rates.
+fn fillers() -> [Vec<u8>; 3] {
+    let high = (0..FILLER.len() as u8).map(|i| 0x80 + 5 * i).collect_vec();
+    let mixed = high
+        .iter()
+        .zip(FILLER)
+        .enumerate()
+        .map(|(i, (h, f))| if i % 2 == 0 { h + 1 } else { *f })
+        .collect();
+
+    [high, FILLER.to_vec(), mixed]
+}
+
+/// Counts, for each possible next byte, how many probes had it among the shortest ciphertexts.
+///
+/// Block padding hides single-byte differences, so we grow some incompressible filler until a
+/// reference (wrong) guess just spills into an extra block: at that point, the right guess
+/// (compressing a bit better) is likely to still fit.
+fn count_votes<F>(oracle: &F, known: &[u8]) -> error::Result<[usize; 256]>
+where
+    F: Fn(&[u8]) -> error::Result<usize>,
+{
+    let window = &known[known.len().saturating_sub(WINDOW)..];
+    let mut votes = [0; 256];
+
+    for filler in fillers() {
+        let mut previous_length = None;
+
+        for filler_len in 0..=filler.len() {
+            let mut prefix = filler[..filler_len].to_vec();
+            prefix.extend(window);
+
+            let mut reference = prefix.clone();
+            reference.push(b'.');
+            let reference_length = oracle(&reference)?;
+            if previous_length.replace(reference_length) >= Some(reference_length) {
+                continue;
+            }
+
+            let lengths: Vec<(u8, usize)> = BASE64_ALPHABET
+                .iter()
+                .chain(b"\n")
+                .map(|&c| {
+                    let mut body = prefix.clone();
+                    body.push(c);
+                    Ok((c, oracle(&body)?))
+                })
+                .collect::<error::Result<_>>()?;
+
+            let min = lengths.iter().map(|(_, l)| *l).min().unwrap_or(0);
+            let winners = lengths.iter().filter(|(_, l)| *l == min).collect_vec();
+            if winners.len() * 4 < lengths.len() {
+                for (c, _) in winners {
+                    votes[*c as usize] += 1;
+                }
+            }
+
+            let mut sorted = votes;
+            sorted.sort_unstable_by(|a, b| b.cmp(a));
+            if sorted[0] >= sorted[1] + 2 {
+                // clear enough winner, no need to keep probing
+                return Ok(votes);
+            }
+        }
+    }
+
+    Ok(votes)
+}
+
+fn top_voted(votes: &[usize; 256]) -> (Vec<u8>, usize) {
+    let max = votes.iter().copied().max().unwrap_or(0);
+    let top = (u8::MIN..=u8::MAX)
+        .filter(|c| votes[*c as usize] == max)
+        .collect();
+
+    (top, max)
+}
+
+/// Recovers the session ID from the length of the (compressed and encrypted) requests alone.
+fn recover_session_id<F>(oracle: F) -> error::Result<String>
+where
+    F: Fn(&[u8]) -> error::Result<usize>,
+{
+    let prefix = "Cookie: sessionid=".as_bytes();
+    let mut known = prefix.to_vec();
+
+    loop {
+        let (mut top, _) = top_voted(&count_votes(&oracle, &known)?);
+
+        if top.len() > 1 {
+            // tied: look one byte further ahead, where only the right guess keeps compressing
+            let mut best_score = 0;
+            let mut best = vec![];
+            for c in top {
+                let mut candidate = known.clone();
+                candidate.push(c);
+                let (_, score) = top_voted(&count_votes(&oracle, &candidate)?);
+
+                match score.cmp(&best_score) {
+                    Ordering::Greater => {
+                        best_score = score;
+                        best = vec![c];
+                    }
+                    Ordering::Equal => best.push(c),
+                    Ordering::Less => {}
+                }
+            }
+            top = best;
+        }
+
+        match top[..] {
+            [b'\n'] => break,
+            [c] => known.push(c),
+            _ => {
+                return Err(CryptopalsError::AttackFailed(
+                    "Could not disambiguate between candidates",
+                ))
+            }
+        }
+    }
+
+    Ok(known[prefix.len()..]
+        .iter()
+        .copied()
+        .map(char::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        assert_eq!(
+            SESSION_ID,
+            recover_session_id(ctr_compression_oracle).unwrap()
+        );
+        assert_eq!(
+            SESSION_ID,
+            recover_session_id(cbc_compression_oracle).unwrap()
+        );
+    }
+
+    #[test]
+    fn aes_ctr_works() {
+        let plaintext = "not a multiple of the block size".as_bytes();
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let ciphertext = aes_ctr(plaintext, key, 42).unwrap();
+
+        assert_eq!(plaintext.len(), ciphertext.len());
+        assert_eq!(plaintext, aes_ctr(&ciphertext, key, 42).unwrap());
+    }
+}
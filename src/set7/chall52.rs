@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error,
+    input::Input,
+    padding,
+};
+
+pub struct Chall52;
+
+impl Challenge for Chall52 {
+    fn number(&self) -> u8 {
+        52
+    }
+
+    fn title(&self) -> &'static str {
+        "Iterated Hash Function Multicollisions"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall52()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Collides", true)
+    }
+}
+
+pub fn solve_chall52() -> error::Result<Output> {
+    let f = MerkleDamgard::truncated_aes(2);
+    let g = MerkleDamgard::truncated_aes(3).with_initial_state(&[0x5a; 3]);
+    let (a, b) = find_cascade_collision(&f, &g)?;
+
+    let hash = cascade_hash(&f, &g, &a)?;
+    let collides = a != b && hash == cascade_hash(&f, &g, &b)?;
+    Ok(Output::new()
+        .with("Message A", a)
+        .with("Message B", b)
+        .with("Hash", hash)
+        .with("Collides", collides))
+}
+
+pub const BLOCK_SIZE: usize = 16;
+
+pub type CompressionFn = fn(&[u8], &[u8]) -> error::Result<Vec<u8>>;
+pub type PaddingFn = fn(&[u8]) -> Vec<u8>;
+pub type BlockPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Iterated hash function: the (padded) message is split into blocks, which are fed one by one
+/// into a compression function along with the current state.
+#[derive(Clone)]
+pub struct MerkleDamgard {
+    state_size: usize,
+    initial_state: Vec<u8>,
+    compress: CompressionFn,
+    pad: PaddingFn,
+}
+
+impl MerkleDamgard {
+    /// Builds a hash with an all-zero initial state. The output of the compression function is
+    /// truncated to the state size.
+    pub fn new(state_size: usize, compress: CompressionFn, pad: PaddingFn) -> Self {
+        Self {
+            state_size,
+            initial_state: vec![0; state_size],
+            compress,
+            pad,
+        }
+    }
+
+    /// Cheap hash whose compression function is AES, keyed by the state, over the block.
+    pub fn truncated_aes(state_size: usize) -> Self {
+        Self::new(state_size, aes_compress, md_pad)
+    }
+
+    pub fn with_initial_state(mut self, initial_state: &[u8]) -> Self {
+        self.initial_state = initial_state.to_vec();
+        self.initial_state.resize(self.state_size, 0);
+        self
+    }
+
+    pub fn state_size(&self) -> usize {
+        self.state_size
+    }
+
+    pub fn initial_state(&self) -> &[u8] {
+        &self.initial_state
+    }
+
+    pub fn pad(&self, message: &[u8]) -> Vec<u8> {
+        (self.pad)(message)
+    }
+
+    pub fn compress(&self, state: &[u8], block: &[u8]) -> error::Result<Vec<u8>> {
+        let mut result = (self.compress)(state, block)?;
+        result.truncate(self.state_size);
+
+        Ok(result)
+    }
+
+    /// Runs the compression function over already-padded blocks, starting from the given state.
+    pub fn compress_blocks(&self, state: &[u8], blocks: &[u8]) -> error::Result<Vec<u8>> {
+        blocks
+            .chunks(BLOCK_SIZE)
+            .try_fold(state.to_vec(), |state, block| self.compress(&state, block))
+    }
+
+    pub fn hash(&self, message: &[u8]) -> error::Result<Vec<u8>> {
+        self.compress_blocks(&self.initial_state, &self.pad(message))
+    }
+}
+
+/// Pads to the block size with a one bit, then zeros, then the message length (in bits).
+pub fn md_pad(message: &[u8]) -> Vec<u8> {
+    let mut result = message.to_vec();
+    result.push(0x80);
+    while result.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        result.push(0);
+    }
+    result.extend((8 * message.len() as u64).to_be_bytes());
+
+    result
+}
+
+fn aes_compress(state: &[u8], block: &[u8]) -> error::Result<Vec<u8>> {
+    block::encrypt_aes_ecb(block, &padding::pad(state, 16)?)
+}
+
+pub fn random_block() -> Vec<u8> {
+    let bytes: [u8; BLOCK_SIZE] = rand::random();
+
+    bytes.to_vec()
+}
+
+/// Finds two different blocks that collide from the given state, returning them along with the
+/// resulting state.
+pub fn find_collision(
+    hash: &MerkleDamgard,
+    state: &[u8],
+) -> error::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let mut seen: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+
+    loop {
+        let block = random_block();
+        let next = hash.compress(state, &block)?;
+
+        match seen.get(&next) {
+            Some(other) if *other != block => return Ok((other.clone(), block, next)),
+            _ => seen.insert(next, block),
+        };
+    }
+}
+
+/// Finds `n` successive block collisions from the given state: choosing either block of each pair
+/// gives 2^n messages with the same state at the end, which is also returned.
+pub fn find_multicollision(
+    hash: &MerkleDamgard,
+    state: &[u8],
+    n: usize,
+) -> error::Result<(BlockPairs, Vec<u8>)> {
+    let mut pairs = vec![];
+    let mut state = state.to_vec();
+
+    for _ in 0..n {
+        let (a, b, next) = find_collision(hash, &state)?;
+        pairs.push((a, b));
+        state = next;
+    }
+
+    Ok((pairs, state))
+}
+
+/// Expands pairs of colliding blocks into all the messages they make up.
+pub fn expand_multicollision(pairs: &[(Vec<u8>, Vec<u8>)]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    pairs
+        .iter()
+        .map(|(a, b)| [a, b])
+        .multi_cartesian_product()
+        .map(|blocks| blocks.into_iter().flatten().copied().collect())
+}
+
+pub fn cascade_hash(
+    f: &MerkleDamgard,
+    g: &MerkleDamgard,
+    message: &[u8],
+) -> error::Result<Vec<u8>> {
+    let mut result = f.hash(message)?;
+    result.extend(g.hash(message)?);
+
+    Ok(result)
+}
+
+/// Finds two different messages with the same `f(m) || g(m)`, by searching a multicollision of
+/// the cheap `f` large enough to probably contain a collision of `g` as well.
+fn find_cascade_collision(
+    f: &MerkleDamgard,
+    g: &MerkleDamgard,
+) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    // 2^(b/2) messages for a b-bit g
+    let n = g.state_size() * 8 / 2;
+    let (mut pairs, mut state) = find_multicollision(f, f.initial_state(), n)?;
+
+    loop {
+        let mut seen = HashMap::new();
+        for message in expand_multicollision(&pairs) {
+            let g_state = g.compress_blocks(g.initial_state(), &message)?;
+            if let Some(other) = seen.insert(g_state, message.clone()) {
+                // same length, so the final padding block doesn't change anything
+                return Ok((other, message));
+            }
+        }
+
+        // no luck, so double the number of messages and try again
+        let (a, b, next) = find_collision(f, &state)?;
+        pairs.push((a, b));
+        state = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding;
+
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let f = MerkleDamgard::truncated_aes(2);
+        let g = MerkleDamgard::truncated_aes(3).with_initial_state(&[0x5a; 3]);
+        let (a, b) = find_cascade_collision(&f, &g).unwrap();
+
+        assert_ne!(a, b);
+        assert_eq!(
+            cascade_hash(&f, &g, &a).unwrap(),
+            cascade_hash(&f, &g, &b).unwrap()
+        );
+    }
+
+    #[test]
+    fn find_multicollision_works() {
+        let f = MerkleDamgard::truncated_aes(2);
+        let (pairs, _) = find_multicollision(&f, f.initial_state(), 4).unwrap();
+        let messages = expand_multicollision(&pairs).collect_vec();
+        let hashes: Vec<_> = messages.iter().map(|m| f.hash(m).unwrap()).collect();
+
+        assert_eq!(16, messages.len());
+        assert_eq!(16, messages.iter().unique().count());
+        assert!(hashes.iter().all_equal());
+    }
+
+    #[test]
+    fn md_pad_works() {
+        let padded = md_pad("YELLOW".as_bytes());
+        assert_eq!(16, padded.len());
+        assert_eq!(
+            "59454c4c4f5780000000000000000030",
+            encoding::bytes_to_hex(&padded)
+        );
+        assert_eq!(32, md_pad(&[0; 8]).len());
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+};
+
+use super::chall52::{self, MerkleDamgard, BLOCK_SIZE};
+
+pub struct Chall53;
+
+impl Challenge for Chall53 {
+    fn number(&self) -> u8 {
+        53
+    }
+
+    fn title(&self) -> &'static str {
+        "Kelsey and Schneier's Expandable Messages"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall53()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches", true)
+    }
+}
+
+pub fn solve_chall53() -> error::Result<Output> {
+    let hash = MerkleDamgard::truncated_aes(3);
+    let k = 10;
+    let message: Vec<u8> = (0..(1 << k) * BLOCK_SIZE).map(|_| rand::random()).collect();
+
+    let (mut collisions, mut bridge_attempts) = (0, 0);
+    let forged = find_second_preimage(&hash, &message, k, |progress| match progress {
+        Progress::ExpandableMessage(i) => collisions = i,
+        Progress::Bridge(attempts) => bridge_attempts = attempts,
+    })?;
+
+    let original_hash = hash.hash(&message)?;
+    let forged_hash = hash.hash(&forged)?;
+    Ok(Output::new()
+        .with("Matches", forged != message && forged_hash == original_hash)
+        .with("Expandable message collisions", format!("{collisions}/{k}"))
+        .with("Bridge attempts", bridge_attempts)
+        .with("Original hash", original_hash)
+        .with("Forged hash", forged_hash))
+}
+
+pub enum Progress {
+    /// Found the given number of collisions (out of `k`) for the expandable message.
+    ExpandableMessage(usize),
+    /// Found a block bridging into the target message, after the given number of attempts.
+    Bridge(usize),
+}
+
+/// Expandable message: the i-th pair collides a single block with `2^(k-1-i) + 1` blocks, so they
+/// can be combined into messages of any length between `k` and `k + 2^k - 1` blocks, all of which
+/// end up in the same state.
+pub struct ExpandableMessage {
+    pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    state: Vec<u8>,
+}
+
+impl ExpandableMessage {
+    pub fn new<F>(hash: &MerkleDamgard, k: usize, mut progress: F) -> error::Result<Self>
+    where
+        F: FnMut(usize),
+    {
+        let mut pairs = vec![];
+        let mut state = hash.initial_state().to_vec();
+
+        for i in 0..k {
+            let dummy = vec![0; (1 << (k - 1 - i)) * BLOCK_SIZE];
+            let dummy_state = hash.compress_blocks(&state, &dummy)?;
+            let (short, long_last, next) = find_collision_from(hash, &state, &dummy_state)?;
+
+            let mut long = dummy;
+            long.extend(long_last);
+            pairs.push((short, long));
+            state = next;
+
+            progress(i + 1);
+        }
+
+        Ok(Self { pairs, state })
+    }
+
+    pub fn min_blocks(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn max_blocks(&self) -> usize {
+        self.pairs.len() + (1 << self.pairs.len()) - 1
+    }
+
+    pub fn state(&self) -> &[u8] {
+        &self.state
+    }
+
+    /// Builds a message with the given number of blocks, or returns `None` if that's not between
+    /// [`Self::min_blocks`] and [`Self::max_blocks`].
+    pub fn expand(&self, blocks: usize) -> Option<Vec<u8>> {
+        if !(self.min_blocks()..=self.max_blocks()).contains(&blocks) {
+            return None;
+        }
+
+        let extra = blocks - self.min_blocks();
+        let k = self.pairs.len();
+
+        let message = self
+            .pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (short, long))| {
+                if extra & (1 << (k - 1 - i)) != 0 {
+                    long.clone()
+                } else {
+                    short.clone()
+                }
+            })
+            .collect();
+
+        Some(message)
+    }
+}
+
+/// Finds a pair of blocks, the first applied to `state_a` and the second to `state_b`, that end
+/// up in the same state, returning them along with that state.
+fn find_collision_from(
+    hash: &MerkleDamgard,
+    state_a: &[u8],
+    state_b: &[u8],
+) -> error::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let mut seen_a: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    let mut seen_b: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+
+    loop {
+        let block_a = chall52::random_block();
+        let next_a = hash.compress(state_a, &block_a)?;
+        if let Some(block_b) = seen_b.get(&next_a) {
+            return Ok((block_a, block_b.clone(), next_a));
+        }
+        seen_a.insert(next_a, block_a);
+
+        let block_b = chall52::random_block();
+        let next_b = hash.compress(state_b, &block_b)?;
+        if let Some(block_a) = seen_a.get(&next_b) {
+            return Ok((block_a.clone(), block_b, next_b));
+        }
+        seen_b.insert(next_b, block_b);
+    }
+}
+
+/// Finds a different message, of the same length, with the same hash as the given (long) one.
+fn find_second_preimage<F>(
+    hash: &MerkleDamgard,
+    message: &[u8],
+    k: usize,
+    mut progress: F,
+) -> error::Result<Vec<u8>>
+where
+    F: FnMut(Progress),
+{
+    if !message.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(CryptopalsError::Unsupported(
+            "Only whole-block messages are supported",
+        ));
+    }
+
+    let expandable = ExpandableMessage::new(hash, k, |i| progress(Progress::ExpandableMessage(i)))?;
+
+    // the states right after each block of the message that we could bridge into, i.e., such
+    // that the expandable message can fill everything before the bridge block
+    let mut targets = HashMap::new();
+    let mut state = hash.initial_state().to_vec();
+    for (i, block) in message.chunks(BLOCK_SIZE).enumerate() {
+        state = hash.compress(&state, block)?;
+        if (expandable.min_blocks()..=expandable.max_blocks()).contains(&i) {
+            targets.entry(state.clone()).or_insert(i);
+        }
+    }
+
+    if targets.is_empty() {
+        return Err(CryptopalsError::Unsupported(
+            "Message is too short for this k",
+        ));
+    }
+
+    let mut attempts = 0;
+    let (bridge, index) = loop {
+        attempts += 1;
+        let block = chall52::random_block();
+        if let Some(&i) = targets.get(&hash.compress(expandable.state(), &block)?) {
+            break (block, i);
+        }
+    };
+    progress(Progress::Bridge(attempts));
+
+    let mut forged = expandable
+        .expand(index)
+        .ok_or(CryptopalsError::AttackFailed(
+            "Bridge is out of the expandable message's bounds",
+        ))?;
+    forged.extend(bridge);
+    forged.extend(&message[(index + 1) * BLOCK_SIZE..]);
+
+    Ok(forged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let hash = MerkleDamgard::truncated_aes(3);
+        let k = 8;
+        let message: Vec<u8> = (0..(1 << k) * BLOCK_SIZE).map(|_| rand::random()).collect();
+
+        let mut collisions = 0;
+        let mut bridges = 0;
+        let forged = find_second_preimage(&hash, &message, k, |progress| match progress {
+            Progress::ExpandableMessage(_) => collisions += 1,
+            Progress::Bridge(_) => bridges += 1,
+        })
+        .unwrap();
+
+        assert_eq!((k, 1), (collisions, bridges));
+        assert_ne!(message, forged);
+        assert_eq!(message.len(), forged.len());
+        assert_eq!(hash.hash(&message).unwrap(), hash.hash(&forged).unwrap());
+    }
+
+    #[test]
+    fn expandable_message_works() {
+        let hash = MerkleDamgard::truncated_aes(2);
+        let expandable = ExpandableMessage::new(&hash, 4, |_| {}).unwrap();
+
+        assert_eq!((4, 19), (expandable.min_blocks(), expandable.max_blocks()));
+        for blocks in expandable.min_blocks()..=expandable.max_blocks() {
+            let message = expandable.expand(blocks).unwrap();
+            assert_eq!(blocks * BLOCK_SIZE, message.len());
+            assert_eq!(
+                expandable.state(),
+                hash.compress_blocks(hash.initial_state(), &message)
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(None, expandable.expand(expandable.min_blocks() - 1));
+        assert_eq!(None, expandable.expand(expandable.max_blocks() + 1));
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    challenge::{Challenge, Output},
+    encoding,
+    error::{self, CryptopalsError},
+    input::Input,
+};
+
+use super::chall52::{self, MerkleDamgard, BLOCK_SIZE};
+
+pub struct Chall54;
+
+impl Challenge for Chall54 {
+    fn number(&self) -> u8 {
+        54
+    }
+
+    fn title(&self) -> &'static str {
+        "Kelsey and Kohno's Nostradamus Attack"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall54()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches prediction", true)
+    }
+}
+
+pub fn solve_chall54() -> error::Result<Output> {
+    let hash = MerkleDamgard::truncated_aes(3);
+    let diamond = DiamondStructure::cached(&hash, 8)?;
+
+    let prefix_blocks = 4;
+    let commitment = diamond.commitment(&hash, prefix_blocks)?;
+
+    let scores = baseball_scores(prefix_blocks * BLOCK_SIZE);
+    let message = diamond.herd(&hash, prefix_blocks, &scores)?;
+    let message_hash = hash.hash(&message)?;
+
+    Ok(Output::new()
+        .with("Prediction", commitment.as_slice())
+        .with(
+            "Message",
+            message.iter().copied().map(char::from).collect::<String>(),
+        )
+        .with("Matches prediction", message_hash == commitment)
+        .with("Hash", message_hash))
+}
+
+/// Tree of collisions: each of the `2^k` leaves is an arbitrary state, and from each node there is
+/// a block leading to its parent, all the way up to a single root state.
+pub struct DiamondStructure {
+    leaves: Vec<Vec<u8>>,
+    /// For each level (from the leaves up), the block and parent index of each node.
+    links: Vec<Vec<(Vec<u8>, usize)>>,
+    root: Vec<u8>,
+}
+
+impl DiamondStructure {
+    pub fn new(hash: &MerkleDamgard, k: usize) -> error::Result<Self> {
+        let leaves = (0..1 << k)
+            .map(|_| (0..hash.state_size()).map(|_| rand::random()).collect_vec())
+            .collect_vec();
+
+        let mut links = vec![];
+        let mut states = leaves.clone();
+        while states.len() > 1 {
+            let (level, next) = collide_pairs(hash, &states)?;
+            links.push(level);
+            states = next;
+        }
+
+        Ok(Self {
+            leaves,
+            links,
+            root: states.remove(0),
+        })
+    }
+
+    /// Building the structure is expensive, so reuse one from the temporary directory if possible
+    /// (building and saving it there otherwise, or if what's there is no good).
+    pub fn cached(hash: &MerkleDamgard, k: usize) -> error::Result<Self> {
+        let path = Self::cache_path(hash, k)?;
+
+        match Self::load(&path, hash) {
+            Ok(diamond) if diamond.k() == k => Ok(diamond),
+            _ => {
+                let diamond = Self::new(hash, k)?;
+                // caching is best-effort, we can always build it again
+                let _ = diamond.save(&path);
+                Ok(diamond)
+            }
+        }
+    }
+
+    /// Where [`Self::cached`] keeps the structure for this hash and `k`. Hashes are told apart by
+    /// what they make of a fixed message, which depends on all of their parts.
+    fn cache_path(hash: &MerkleDamgard, k: usize) -> error::Result<PathBuf> {
+        let fingerprint = hash.hash(b"diamond structure")?;
+
+        Ok(env::temp_dir().join(format!(
+            "cryptopals-chall54-{}-{}-{k}.txt",
+            hash.state_size() * 8,
+            encoding::bytes_to_hex(&fingerprint)
+        )))
+    }
+
+    pub fn k(&self) -> usize {
+        self.links.len()
+    }
+
+    /// The hash of any message with the given number of blocks before the diamond structure.
+    pub fn commitment(&self, hash: &MerkleDamgard, prefix_blocks: usize) -> error::Result<Vec<u8>> {
+        // prefix, then a block linking it to a leaf, then one block for each level
+        let message_len = (prefix_blocks + 1 + self.k()) * BLOCK_SIZE;
+        let padded = hash.pad(&vec![0; message_len]);
+
+        hash.compress_blocks(&self.root, &padded[message_len..])
+    }
+
+    /// Builds a message starting with the given prefix (padded with spaces to the given number of
+    /// blocks) whose hash is the commitment.
+    pub fn herd(
+        &self,
+        hash: &MerkleDamgard,
+        prefix_blocks: usize,
+        prefix: &[u8],
+    ) -> error::Result<Vec<u8>> {
+        if prefix.len() > prefix_blocks * BLOCK_SIZE {
+            return Err(CryptopalsError::Unsupported("Prefix is too long"));
+        }
+
+        let mut message = prefix.to_vec();
+        message.resize(prefix_blocks * BLOCK_SIZE, b' ');
+        let state = hash.compress_blocks(hash.initial_state(), &message)?;
+
+        let leaves: HashMap<_, _> = self
+            .leaves
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (l, i))
+            .collect();
+        let mut node = loop {
+            let block = chall52::random_block();
+            if let Some(&i) = leaves.get(&hash.compress(&state, &block)?) {
+                message.extend(block);
+                break i;
+            }
+        };
+
+        for level in &self.links {
+            let (block, parent) = &level[node];
+            message.extend(block);
+            node = *parent;
+        }
+
+        Ok(message)
+    }
+
+    /// Writes the structure to a file, as hex: the leaves, then one line for each level, then the
+    /// root. The file is written under another name first and then renamed, so that whoever reads
+    /// it concurrently sees all of it or nothing.
+    pub fn save(&self, path: &Path) -> error::Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(format!(".{:016x}.tmp", rand::random::<u64>()));
+        let temp = PathBuf::from(temp);
+
+        let result = self
+            .write(&temp)
+            .and_then(|()| Ok(fs::rename(&temp, path)?));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+
+        result
+    }
+
+    fn write(&self, path: &Path) -> error::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+
+        writeln!(
+            file,
+            "{}",
+            self.leaves
+                .iter()
+                .map(|l| encoding::bytes_to_hex(l))
+                .join(" ")
+        )?;
+        for level in &self.links {
+            let line = level
+                .iter()
+                .map(|(block, parent)| format!("{}:{parent}", encoding::bytes_to_hex(block)))
+                .join(" ");
+            writeln!(file, "{line}")?;
+        }
+        writeln!(file, "{}", encoding::bytes_to_hex(&self.root))?;
+
+        Ok(file.flush()?)
+    }
+
+    /// Reads a structure previously written with [`Self::save`] for the given hash, checking that
+    /// it's whole and that all of its blocks do lead up to its root.
+    pub fn load(path: &Path, hash: &MerkleDamgard) -> error::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid diamond structure");
+
+        let lines: Vec<String> = io::BufReader::new(fs::File::open(path)?)
+            .lines()
+            .collect::<io::Result<_>>()?;
+        let (first, rest) = lines.split_first().ok_or_else(invalid)?;
+        let (last, levels) = rest.split_last().ok_or_else(invalid)?;
+
+        let leaves = first
+            .split(' ')
+            .map(encoding::hex_to_bytes)
+            .collect::<error::Result<_>>()?;
+        let links = levels
+            .iter()
+            .map(|line| {
+                line.split(' ')
+                    .map(|node| {
+                        let (block, parent) = node.split_once(':').ok_or_else(invalid)?;
+                        let parent = parent.parse().map_err(|_| invalid())?;
+                        Ok((encoding::hex_to_bytes(block)?, parent))
+                    })
+                    .collect::<error::Result<_>>()
+            })
+            .collect::<error::Result<_>>()?;
+
+        let diamond = Self {
+            leaves,
+            links,
+            root: encoding::hex_to_bytes(last)?,
+        };
+        if !diamond.is_valid(hash)? {
+            return Err(invalid().into());
+        }
+
+        Ok(diamond)
+    }
+
+    /// Whether each level pairs up the nodes of the one below it with blocks that collide, from
+    /// `2^k` leaves up to the root.
+    fn is_valid(&self, hash: &MerkleDamgard) -> error::Result<bool> {
+        let state_size = hash.state_size();
+        if self.leaves.len() != 1 << self.k()
+            || self.leaves.iter().any(|leaf| leaf.len() != state_size)
+        {
+            return Ok(false);
+        }
+
+        let mut states = self.leaves.clone();
+        for level in &self.links {
+            if level.len() != states.len() {
+                return Ok(false);
+            }
+
+            let mut parents: Vec<Option<Vec<u8>>> = vec![None; states.len() / 2];
+            let mut children = vec![0; parents.len()];
+            for ((block, parent), state) in level.iter().zip(&states) {
+                if block.len() != BLOCK_SIZE || *parent >= parents.len() {
+                    return Ok(false);
+                }
+
+                let next = hash.compress(state, block)?;
+                match &parents[*parent] {
+                    Some(other) if *other != next => return Ok(false),
+                    _ => parents[*parent] = Some(next),
+                }
+                children[*parent] += 1;
+            }
+            if children.iter().any(|&count| count != 2) {
+                return Ok(false);
+            }
+
+            states = parents.into_iter().flatten().collect();
+        }
+
+        Ok(states == [self.root.clone()])
+    }
+}
+
+/// Pairs up all the given states by finding, for each pair, two blocks leading to the same state.
+/// Returns the block and parent index for each state, along with the parent states.
+#[allow(clippy::type_complexity)]
+fn collide_pairs(
+    hash: &MerkleDamgard,
+    states: &[Vec<u8>],
+) -> error::Result<(Vec<(Vec<u8>, usize)>, Vec<Vec<u8>>)> {
+    let mut links = vec![None; states.len()];
+    let mut parents = vec![];
+    let mut seen: HashMap<Vec<u8>, (usize, Vec<u8>)> = HashMap::new();
+
+    // rather than colliding fixed pairs, try all unpaired states at once and pair up whichever
+    // two collide first, which is much faster
+    while parents.len() < states.len() / 2 {
+        for (i, state) in states.iter().enumerate() {
+            if links[i].is_some() {
+                continue;
+            }
+
+            let block = chall52::random_block();
+            let next = hash.compress(state, &block)?;
+
+            match seen.get(&next) {
+                Some((j, other)) if *j != i && links[*j].is_none() => {
+                    links[*j] = Some((other.clone(), parents.len()));
+                    links[i] = Some((block, parents.len()));
+                    parents.push(next);
+                }
+                _ => {
+                    seen.insert(next, (i, block));
+                }
+            }
+        }
+    }
+
+    Ok((links.into_iter().map(Option::unwrap).collect(), parents))
+}
+
+fn baseball_scores(max_len: usize) -> Vec<u8> {
+    let teams = ["Dodgers", "Yankees", "Red Sox", "Mets", "Cubs", "Giants"];
+    let mut rng = rand::thread_rng();
+    let mut result = String::new();
+
+    loop {
+        let mut pair = teams.choose_multiple(&mut rng, 2);
+        let game = format!(
+            "{} {}, {} {}; ",
+            pair.next().unwrap(),
+            rng.gen_range(0..10),
+            pair.next().unwrap(),
+            rng.gen_range(0..10)
+        );
+        if result.len() + game.len() > max_len {
+            break;
+        }
+        result.push_str(&game);
+    }
+
+    result.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file name in the temporary directory that no other test (or test run) uses.
+    fn unique_temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!(
+            "cryptopals-chall54-{name}-{}-{:016x}.txt",
+            std::process::id(),
+            rand::random::<u64>()
+        ))
+    }
+
+    #[test]
+    fn is_correct() {
+        let hash = MerkleDamgard::truncated_aes(3);
+        let diamond = DiamondStructure::cached(&hash, 8).unwrap();
+        let commitment = diamond.commitment(&hash, 4).unwrap();
+
+        for _ in 0..2 {
+            let scores = baseball_scores(4 * BLOCK_SIZE);
+            let message = diamond.herd(&hash, 4, &scores).unwrap();
+
+            assert!(message.starts_with(&scores));
+            assert_eq!(commitment, hash.hash(&message).unwrap());
+        }
+    }
+
+    #[test]
+    fn save_then_load_works() {
+        let hash = MerkleDamgard::truncated_aes(2);
+        let diamond = DiamondStructure::new(&hash, 3).unwrap();
+        let path = unique_temp_path("roundtrip");
+        diamond.save(&path).unwrap();
+        let loaded = DiamondStructure::load(&path, &hash);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(diamond.leaves, loaded.leaves);
+        assert_eq!(diamond.links, loaded.links);
+        assert_eq!(diamond.root, loaded.root);
+        assert_eq!(
+            diamond.commitment(&hash, 1).unwrap(),
+            loaded.commitment(&hash, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn load_rejects_bad_structures() {
+        let hash = MerkleDamgard::truncated_aes(2);
+        let diamond = DiamondStructure::new(&hash, 3).unwrap();
+        let path = unique_temp_path("corrupt");
+        diamond.save(&path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = saved.lines().collect();
+
+        let mut corruptions = vec![
+            // cut short, or not even hex
+            lines[..2].join("\n"),
+            saved.replacen(' ', "zz ", 1),
+            // a parent that doesn't exist
+            saved.replacen(":0", ":9", 1),
+        ];
+        // a level that doesn't halve the one below it
+        let mut extra_level = lines.clone();
+        extra_level.insert(2, lines[1]);
+        corruptions.push(extra_level.join("\n"));
+        // a block that no longer collides
+        let (block, _) = &diamond.links[0][0];
+        let mut flipped = block.clone();
+        flipped[0] ^= 1;
+        corruptions.push(saved.replacen(
+            &encoding::bytes_to_hex(block),
+            &encoding::bytes_to_hex(&flipped),
+            1,
+        ));
+
+        let loaded: Vec<_> = corruptions
+            .into_iter()
+            .map(|corrupted| {
+                fs::write(&path, corrupted).unwrap();
+                DiamondStructure::load(&path, &hash)
+            })
+            .collect();
+        fs::remove_file(&path).unwrap();
+
+        assert!(loaded.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn cache_depends_on_the_hash() {
+        let hash = MerkleDamgard::truncated_aes(2);
+        let other = hash.clone().with_initial_state(b"xy");
+
+        assert_ne!(
+            DiamondStructure::cache_path(&hash, 3).unwrap(),
+            DiamondStructure::cache_path(&other, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn cached_rebuilds_bad_cache() {
+        let hash = MerkleDamgard::truncated_aes(2).with_initial_state(b"ab");
+        let path = DiamondStructure::cache_path(&hash, 2).unwrap();
+        fs::write(&path, "not a diamond structure").unwrap();
+
+        let diamond = DiamondStructure::cached(&hash, 2).unwrap();
+        let reloaded = DiamondStructure::load(&path, &hash);
+        fs::remove_file(&path).unwrap();
+
+        assert!(diamond.is_valid(&hash).unwrap());
+        assert!(reloaded.is_ok());
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    challenge::{Challenge, Output},
+    error,
+    input::Input,
+};
+
+pub struct Chall55;
+
+impl Challenge for Chall55 {
+    fn number(&self) -> u8 {
+        55
+    }
+
+    fn title(&self) -> &'static str {
+        "MD4 Collisions"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall55()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Collides", true)
+    }
+}
+
+pub fn solve_chall55() -> error::Result<Output> {
+    let (attempts, a, b) = find_collision();
+
+    Ok(Output::new()
+        .with("Attempts", attempts)
+        .with("Collides", a != b && md4(&a) == md4(&b))
+        .with("MD4 A", md4(&a))
+        .with("MD4 B", md4(&b))
+        .with("Message A", a)
+        .with("Message B", b))
+}
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// Message word used by each of the 48 steps.
+const WORD_INDEXES: [usize; 48] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15, //
+    0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15,
+];
+
+/// Rotation amounts for each round, cycling through the four steps of each group.
+const SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+const ROUND_CONSTANTS: [u32; 3] = [0, 0x5a827999, 0x6ed9eba1];
+
+/// All the words computed by MD4: the initial state (in a, d, c, b order), followed by the word
+/// computed by each step (a1, d1, c1, b1, a2, ...). So step `s` computes `words[s + 4]`, replacing
+/// `words[s]`.
+pub type Md4Words = [u32; 52];
+
+fn round_function(s: usize, b: u32, c: u32, d: u32) -> u32 {
+    match s / 16 {
+        0 => (b & c) | (!b & d),
+        1 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+/// Computes the word for step `s`, given the words of all previous steps.
+fn step(words: &[u32], s: usize, message: &[u32; 16]) -> u32 {
+    let (a, b, c, d) = (words[s], words[s + 3], words[s + 2], words[s + 1]);
+
+    a.wrapping_add(round_function(s, b, c, d))
+        .wrapping_add(message[WORD_INDEXES[s]])
+        .wrapping_add(ROUND_CONSTANTS[s / 16])
+        .rotate_left(SHIFTS[s / 16][s % 4])
+}
+
+/// Inverse of [`step`] for the first round: the message word that leads to the (already chosen)
+/// word of step `s`.
+fn message_word(words: &[u32], s: usize) -> u32 {
+    let (a, b, c, d) = (words[s], words[s + 3], words[s + 2], words[s + 1]);
+
+    words[s + 4]
+        .rotate_right(SHIFTS[0][s % 4])
+        .wrapping_sub(a)
+        .wrapping_sub(round_function(s, b, c, d))
+}
+
+fn initial_words(state: &[u32; 4]) -> Md4Words {
+    let mut words = [0; 52];
+    words[..4].copy_from_slice(&[state[0], state[3], state[2], state[1]]);
+
+    words
+}
+
+/// Runs the MD4 compression function step by step, returning all the intermediate words.
+pub fn md4_steps(state: &[u32; 4], message: &[u32; 16]) -> Md4Words {
+    let mut words = initial_words(state);
+    for s in 0..48 {
+        words[s + 4] = step(&words, s, message);
+    }
+
+    words
+}
+
+pub fn md4_compress(state: &[u32; 4], message: &[u32; 16]) -> [u32; 4] {
+    let words = md4_steps(state, message);
+
+    [
+        state[0].wrapping_add(words[48]),
+        state[1].wrapping_add(words[51]),
+        state[2].wrapping_add(words[50]),
+        state[3].wrapping_add(words[49]),
+    ]
+}
+
+fn to_words(block: &[u8]) -> [u32; 16] {
+    let mut words = [0; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("Block must have 64 bytes"));
+    }
+
+    words
+}
+
+fn to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+pub fn md4(message: &[u8]) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend((8 * message.len() as u64).to_le_bytes());
+
+    let state = padded.chunks(64).fold(INITIAL_STATE, |state, block| {
+        md4_compress(&state, &to_words(block))
+    });
+
+    to_bytes(&state)
+}
+
+#[derive(Clone, Copy)]
+enum Bit {
+    Zero,
+    One,
+    /// Equal to the same bit of the word computed the given number of steps earlier.
+    Same(usize),
+    /// The opposite of the same bit of the word computed the given number of steps earlier.
+    Flipped(usize),
+}
+
+use Bit::*;
+
+/// Wang et al.'s sufficient conditions for the differential to hold, as (step, bit, value), with
+/// bits numbered from 1 as in the paper.
+#[rustfmt::skip]
+const CONDITIONS: &[(usize, u32, Bit)] = &[
+    // a1, d1, c1, b1
+    (0, 7, Same(1)),
+    (1, 7, Zero), (1, 8, Same(1)), (1, 11, Same(1)),
+    (2, 7, One), (2, 8, One), (2, 11, Zero), (2, 26, Same(1)),
+    (3, 7, One), (3, 8, Zero), (3, 11, Zero), (3, 26, Zero),
+    // a2, d2, c2, b2
+    (4, 8, One), (4, 11, One), (4, 14, Same(1)), (4, 26, Zero),
+    (5, 14, Zero), (5, 19, Same(1)), (5, 20, Same(1)), (5, 21, Same(1)), (5, 22, Same(1)),
+    (5, 26, One),
+    (6, 13, Same(1)), (6, 14, Zero), (6, 15, Same(1)), (6, 19, Zero), (6, 20, Zero), (6, 21, One),
+    (6, 22, Zero),
+    (7, 13, One), (7, 14, One), (7, 15, Zero), (7, 17, Same(1)), (7, 19, Zero), (7, 20, Zero),
+    (7, 21, Zero), (7, 22, Zero),
+    // a3, d3, c3, b3
+    (8, 13, One), (8, 14, One), (8, 15, One), (8, 17, Zero), (8, 19, Zero), (8, 20, Zero),
+    (8, 21, Zero), (8, 22, One), (8, 23, Same(1)), (8, 26, Same(1)),
+    (9, 13, One), (9, 14, One), (9, 15, One), (9, 17, Zero), (9, 20, Zero), (9, 21, One),
+    (9, 22, One), (9, 23, Zero), (9, 26, One), (9, 30, Same(1)),
+    (10, 17, One), (10, 20, Zero), (10, 21, Zero), (10, 22, Zero), (10, 23, Zero), (10, 26, Zero),
+    (10, 30, One), (10, 32, Same(1)),
+    (11, 20, Zero), (11, 21, One), (11, 22, One), (11, 23, Same(1)), (11, 26, One), (11, 30, Zero),
+    (11, 32, Zero),
+    // a4, d4, c4, b4
+    (12, 23, Zero), (12, 26, Zero), (12, 27, Same(1)), (12, 29, Same(1)), (12, 30, One),
+    (12, 32, Zero),
+    (13, 23, Zero), (13, 26, Zero), (13, 27, One), (13, 29, One), (13, 30, Zero), (13, 32, One),
+    (14, 19, Same(1)), (14, 23, One), (14, 26, One), (14, 27, Zero), (14, 29, Zero), (14, 30, Zero),
+    (15, 19, Zero), (15, 26, One), (15, 27, One), (15, 29, One), (15, 30, Zero),
+    // a5, d5, c5, b5
+    (16, 19, Same(2)), (16, 26, One), (16, 27, Zero), (16, 29, One), (16, 32, One),
+    (17, 19, Same(1)), (17, 26, Same(2)), (17, 27, Same(2)), (17, 29, Same(2)), (17, 32, Same(2)),
+    (18, 26, Same(1)), (18, 27, Same(1)), (18, 29, Same(1)), (18, 30, Same(1)), (18, 32, Same(1)),
+    (19, 29, Same(1)), (19, 30, One), (19, 32, Zero),
+    // a6, d6, c6
+    (20, 29, One), (20, 32, One),
+    (21, 29, Same(2)),
+    (22, 29, Same(1)), (22, 30, Flipped(1)), (22, 32, Flipped(1)),
+    // b9, a10
+    (35, 32, One),
+    (36, 32, One),
+];
+
+fn conditions_for(s: usize) -> impl Iterator<Item = (u32, Bit)> {
+    CONDITIONS
+        .iter()
+        .filter(move |(t, _, _)| *t == s)
+        .map(|&(_, bit, value)| (bit, value))
+}
+
+fn mask(bit: u32) -> u32 {
+    1 << (bit - 1)
+}
+
+/// The value the given bit of the word of step `s` must have (as a masked word).
+fn wanted(words: &[u32], s: usize, bit: u32, value: Bit) -> u32 {
+    match value {
+        Zero => 0,
+        One => mask(bit),
+        Same(back) => words[s + 4 - back] & mask(bit),
+        Flipped(back) => !words[s + 4 - back] & mask(bit),
+    }
+}
+
+fn holds(words: &[u32], s: usize, bit: u32, value: Bit) -> bool {
+    words[s + 4] & mask(bit) == wanted(words, s, bit, value)
+}
+
+/// Whether any condition depends on the given bit of the word of step `s`.
+fn is_constrained(s: usize, bit: u32) -> bool {
+    CONDITIONS.iter().any(|&(t, b, value)| {
+        b == bit
+            && match value {
+                Same(back) | Flipped(back) => t == s || t - back == s,
+                Zero | One => t == s,
+            }
+    })
+}
+
+/// Single-step modification: fixes each word of the first round to satisfy its conditions, and
+/// then solves for the message word that leads to it.
+fn modify_first_round(message: &mut [u32; 16], words: &mut Md4Words) {
+    for s in 0..16 {
+        let mut word = step(words, s, message);
+        for (bit, value) in conditions_for(s) {
+            word = (word & !mask(bit)) | wanted(words, s, bit, value);
+        }
+        words[s + 4] = word;
+        message[s] = message_word(words, s);
+    }
+}
+
+/// Multi-step modification: fixes the conditions on a5, d5 and c5 by flipping the matching bit of
+/// the first round word computed from the same message word, and then adjusting the next four
+/// message words so that every other word of the first round stays the same.
+///
+/// This doesn't work for b5, as its message word is used by a4, which is an input to a5.
+fn modify_second_round(message: &mut [u32; 16], words: &mut Md4Words) {
+    for s in 16..19 {
+        let k = WORD_INDEXES[s];
+
+        for (bit, value) in conditions_for(s) {
+            words[s + 4] = step(words, s, message);
+            if holds(words, s, bit, value) {
+                continue;
+            }
+
+            // the bit that, once rotated out of step k and into step s, lands on this one
+            let target = (bit - 1 + 32 - SHIFTS[1][s % 4] + SHIFTS[0][k % 4]) % 32 + 1;
+            if is_constrained(k, target) {
+                continue;
+            }
+
+            words[k + 4] ^= mask(target);
+            for (t, word) in message.iter_mut().enumerate().skip(k).take(5) {
+                *word = message_word(words, t);
+            }
+        }
+
+        words[s + 4] = step(words, s, message);
+    }
+}
+
+/// The other message of the pair, following Wang et al.'s differential.
+fn apply_differential(message: &[u32; 16]) -> [u32; 16] {
+    let mut result = *message;
+    result[1] = result[1].wrapping_add(1 << 31);
+    result[2] = result[2].wrapping_add((1 << 31) - (1 << 28));
+    result[12] = result[12].wrapping_sub(1 << 16);
+
+    result
+}
+
+/// Finds two different single-block messages with the same MD4 hash, returning them along with the
+/// number of attempts it took.
+fn find_collision() -> (usize, Vec<u8>, Vec<u8>) {
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        let mut message = rand::random();
+        let mut words = initial_words(&INITIAL_STATE);
+        modify_first_round(&mut message, &mut words);
+        modify_second_round(&mut message, &mut words);
+
+        let other = apply_differential(&message);
+        if md4_compress(&INITIAL_STATE, &message) == md4_compress(&INITIAL_STATE, &other) {
+            return (attempts, to_bytes(&message), to_bytes(&other));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding;
+
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let (_, a, b) = find_collision();
+
+        assert_ne!(a, b);
+        assert_eq!(64, a.len());
+        assert_eq!(md4(&a), md4(&b));
+    }
+
+    #[test]
+    fn md4_works() {
+        // test vectors from RFC 1320
+        let cases = [
+            ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+            ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+            ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+            ("message digest", "d9130a8164549fe818874806e1c7014b"),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "e33b4ddc9c38f2199c3e7b164fcc0536",
+            ),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(expected, encoding::bytes_to_hex(&md4(message.as_bytes())));
+        }
+    }
+
+    #[test]
+    fn modifications_satisfy_conditions() {
+        let mut message = rand::random();
+        let mut words = initial_words(&INITIAL_STATE);
+        modify_first_round(&mut message, &mut words);
+
+        assert_eq!(md4_steps(&INITIAL_STATE, &message)[..20], words[..20]);
+        for &(s, bit, value) in CONDITIONS.iter().filter(|(s, _, _)| *s < 16) {
+            assert!(holds(&words, s, bit, value), "step {s}, bit {bit}");
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    challenge::{Challenge, Output},
+    encoding,
+    error::{self, CryptopalsError},
+    input::Input,
+};
+
+const SLOW: bool = true;
+
+pub struct Chall56;
+
+impl Challenge for Chall56 {
+    fn number(&self) -> u8 {
+        56
+    }
+
+    fn title(&self) -> &'static str {
+        "RC4 Single-Byte Biases"
+    }
+
+    fn slow(&self) -> bool {
+        SLOW
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall56()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Cookie", "BE SURE TO DRINK YOUR OVALTINE")
+    }
+}
+
+pub fn solve_chall56() -> error::Result<Output> {
+    let cookie = encoding::base64_decode("QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F")?;
+
+    attack(&cookie, 1 << 24, rand::random())
+}
+
+/// Recovers the cookie from an [`rc4_oracle`] hiding it, with the given number of samples per
+/// padding length, and the seed that makes it all deterministic.
+fn attack(cookie: &[u8], samples: usize, seed: u64) -> error::Result<Output> {
+    let queries = AtomicUsize::new(0);
+    let oracle = rc4_oracle(cookie);
+    let oracle = |request: &[u8], rng: &mut StdRng| {
+        queries.fetch_add(1, Ordering::Relaxed);
+        oracle(request, rng)
+    };
+    let cookie = recover_cookie(&oracle, samples, seed)?;
+    Ok(Output::new()
+        .with(
+            "Cookie",
+            cookie.iter().copied().map(char::from).collect::<String>(),
+        )
+        .with("Queries", queries.into_inner()))
+}
+
+pub struct Rc4 {
+    state: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    /// Runs the key schedule, on a key of 1 to 256 bytes.
+    pub fn new(key: &[u8]) -> error::Result<Self> {
+        if !(1..=256).contains(&key.len()) {
+            return Err(CryptopalsError::InvalidParameter(
+                "RC4 keys must be 1 to 256 bytes long",
+            ));
+        }
+
+        let mut state = [0; 256];
+        for (i, x) in state.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+            state.swap(i, j as usize);
+        }
+
+        Ok(Self { state, i: 0, j: 0 })
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.state[self.i as usize]);
+        self.state.swap(self.i as usize, self.j as usize);
+
+        self.state[self.state[self.i as usize].wrapping_add(self.state[self.j as usize]) as usize]
+    }
+
+    /// Encrypts (or decrypts) by XORing with the next bytes of the keystream.
+    pub fn apply(&mut self, text: &[u8]) -> Vec<u8> {
+        text.iter().map(|x| x ^ self.next_byte()).collect()
+    }
+}
+
+/// Encrypts the request followed by the secret cookie, under a fresh random key each time.
+fn rc4_oracle(cookie: &[u8]) -> impl Fn(&[u8], &mut StdRng) -> Vec<u8> + Sync + '_ {
+    move |request, rng| {
+        let key: [u8; 16] = rng.gen();
+        let mut plaintext = request.to_vec();
+        plaintext.extend(cookie);
+
+        Rc4::new(&key)
+            .expect("16-byte keys are valid")
+            .apply(&plaintext)
+    }
+}
+
+/// The (0-based) keystream positions of the biases we look at, the value they are biased towards,
+/// and (roughly) how much more likely that value is than any other.
+const BIASES: [(usize, u8, f64); 2] = [(15, 240, 0.0359), (31, 224, 0.0254)];
+
+/// Each chunk of samples gets its own RNG, so that results don't depend on the number of threads.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Counts each value of the ciphertext bytes at the biased positions, over the given number of
+/// encryptions of the same request.
+fn count_biased_bytes<F>(oracle: &F, request: &[u8], samples: usize, seed: u64) -> [[u64; 256]; 2]
+where
+    F: Fn(&[u8], &mut StdRng) -> Vec<u8> + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunks = samples.div_ceil(CHUNK_SIZE);
+    let next_chunk = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut counts = [[0; 256]; 2];
+
+                    loop {
+                        let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        if chunk >= chunks {
+                            return counts;
+                        }
+
+                        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(chunk as u64));
+                        for _ in 0..CHUNK_SIZE.min(samples - chunk * CHUNK_SIZE) {
+                            let ciphertext = oracle(request, &mut rng);
+                            for (counts, (position, _, _)) in counts.iter_mut().zip(BIASES) {
+                                if let Some(&c) = ciphertext.get(position) {
+                                    counts[c as usize] += 1;
+                                }
+                            }
+                        }
+                    }
+                })
+            })
+            .collect_vec();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Thread panicked"))
+            .fold([[0; 256]; 2], |mut total, counts| {
+                for (total, counts) in total.iter_mut().zip(counts) {
+                    for (t, c) in total.iter_mut().zip(counts) {
+                        *t += c;
+                    }
+                }
+                total
+            })
+    })
+}
+
+/// Recovers the secret appended to every request from the keystream biases alone, by padding the
+/// request so that each secret byte lands on a biased position, over the given number of samples
+/// per padding length. The seed makes the whole process deterministic.
+fn recover_cookie<F>(oracle: &F, samples: usize, seed: u64) -> error::Result<Vec<u8>>
+where
+    F: Fn(&[u8], &mut StdRng) -> Vec<u8> + Sync,
+{
+    let cookie_len = oracle(&[], &mut StdRng::seed_from_u64(seed)).len();
+    if cookie_len > 32 {
+        return Err(CryptopalsError::Unsupported(
+            "Only cookies of up to 32 bytes are supported",
+        ));
+    }
+
+    // log-likelihood of each value for each byte of the cookie
+    let mut scores = vec![[0.0; 256]; cookie_len];
+
+    for padding in 0..32 {
+        let lands = |position: usize| (padding..padding + cookie_len).contains(&position);
+        if !BIASES.iter().any(|&(position, _, _)| lands(position)) {
+            continue;
+        }
+
+        let request = vec![b'A'; padding];
+        let seed = seed.wrapping_add((padding as u64) << 32);
+        let counts = count_biased_bytes(oracle, &request, samples, seed);
+
+        for (counts, (position, bias, excess)) in counts.iter().zip(BIASES) {
+            if !lands(position) {
+                continue;
+            }
+
+            for (c, score) in scores[position - padding].iter_mut().enumerate() {
+                *score += counts[c ^ bias as usize] as f64 * (1.0 + excess).ln();
+            }
+        }
+    }
+
+    Ok(scores
+        .iter()
+        .map(|score| {
+            (0..=255)
+                .max_by(|&a, &b| score[a as usize].total_cmp(&score[b as usize]))
+                .unwrap_or(0)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        // the real attack, but on a 1-byte cookie and with a quarter of the samples so that it runs
+        // quickly; that's usually enough, and the seed is fixed so the outcome is too
+        let output = attack("?".as_bytes(), 1 << 22, 56).unwrap();
+
+        assert!(output.matches(&Output::new().with("Cookie", "?")));
+    }
+
+    #[test]
+    fn rc4_works() {
+        // test vectors from RFC 6229
+        let mut rc4 = Rc4::new(&[1, 2, 3, 4, 5]).unwrap();
+        let keystream = (0..16).map(|_| rc4.next_byte()).collect_vec();
+        assert_eq!(
+            "b2396305f03dc027ccc3524a0a1118a8",
+            crate::encoding::bytes_to_hex(&keystream)
+        );
+
+        let plaintext = "Attack at dawn".as_bytes();
+        let ciphertext = Rc4::new("Secret".as_bytes()).unwrap().apply(plaintext);
+        assert_eq!(
+            "45a01f645fc35b383552544b9bf5",
+            crate::encoding::bytes_to_hex(&ciphertext)
+        );
+        assert_eq!(
+            plaintext,
+            Rc4::new("Secret".as_bytes()).unwrap().apply(&ciphertext)
+        );
+    }
+
+    #[test]
+    fn rc4_rejects_empty_key() {
+        assert!(matches!(
+            Rc4::new(&[]),
+            Err(CryptopalsError::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,250 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    numtheory,
+};
+
+pub struct Chall57;
+
+impl Challenge for Chall57 {
+    fn number(&self) -> u8 {
+        57
+    }
+
+    fn title(&self) -> &'static str {
+        "Diffie-Hellman Revisited: Small Subgroup Confinement"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall57()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches public key", true)
+    }
+}
+
+pub fn solve_chall57() -> error::Result<Output> {
+    let public_key = group().public_key(bob_private_key());
+    let queries = Cell::new(0_usize);
+    let private_key = recover_private_key(group(), |h| {
+        queries.set(queries.get() + 1);
+        bob(h)
+    })?;
+
+    Ok(Output::new()
+        .with(
+            "Matches public key",
+            group().public_key(&private_key) == public_key,
+        )
+        .with("Bob's public key", public_key)
+        .with("Recovered private key", private_key)
+        .with("Queries", queries.get()))
+}
+
+/// Diffie-Hellman group: `g` generates a subgroup of (prime) order `q` modulo `p`.
+#[derive(Clone)]
+pub struct DhGroup {
+    p: BigUint,
+    g: BigUint,
+    q: BigUint,
+}
+
+impl DhGroup {
+    pub fn new(p: BigUint, g: BigUint, q: BigUint) -> Self {
+        Self { p, g, q }
+    }
+
+    /// Parses a group from decimal strings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the strings is not a valid decimal number.
+    pub fn from_decimal(p: &str, g: &str, q: &str) -> Self {
+        let parse = |n: &str| BigUint::parse_bytes(n.as_bytes(), 10).expect("Invalid number");
+
+        Self::new(parse(p), parse(g), parse(q))
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    pub fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    pub fn random_private_key(&self) -> BigUint {
+        rand::thread_rng().gen_biguint_range(&BigUint::one(), &self.q)
+    }
+
+    pub fn public_key(&self, private_key: &BigUint) -> BigUint {
+        self.g.modpow(private_key, &self.p)
+    }
+
+    /// Finds a random element of order `r` modulo `p`, which must be a prime dividing `p - 1`.
+    pub fn element_of_order(&self, r: &BigUint) -> BigUint {
+        let exponent = (&self.p - 1u32) / r;
+
+        loop {
+            let h = rand::thread_rng()
+                .gen_biguint_range(&BigUint::one(), &self.p)
+                .modpow(&exponent, &self.p);
+            if !h.is_one() {
+                return h;
+            }
+        }
+    }
+}
+
+impl numtheory::Group for DhGroup {
+    type Element = BigUint;
+
+    fn op(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.p
+    }
+
+    fn pow(&self, a: &BigUint, exponent: &BigUint) -> BigUint {
+        a.modpow(exponent, &self.p)
+    }
+
+    fn hash(&self, a: &BigUint) -> u64 {
+        a.iter_u64_digits().next().unwrap_or(0)
+    }
+}
+
+static GROUP: OnceLock<DhGroup> = OnceLock::new();
+
+pub fn group() -> &'static DhGroup {
+    GROUP.get_or_init(|| {
+        DhGroup::from_decimal(
+            concat!(
+                "7199773997391911030609999317773941274322764333428698921736339643928346453700085358",
+                "802973900485592910475480089726140708102474957429903531369589969318716771"
+            ),
+            concat!(
+                "4565356397095740655436854503483826832136106141639563487732438195343690437606117828",
+                "318042418238184896212352329118608100083187535033402010599512641674644143"
+            ),
+            "236234353446506858198510045061214171961",
+        )
+    })
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> error::Result<Vec<u8>> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(message)?;
+
+    Ok(signer.sign_to_vec()?)
+}
+
+static BOB_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn bob_private_key() -> &'static BigUint {
+    BOB_PRIVATE_KEY.get_or_init(|| group().random_private_key())
+}
+
+/// Bob's side of the protocol: given someone's public key, replies with a message and its MAC,
+/// keyed by the shared secret.
+fn bob(public_key: &BigUint) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    let shared_secret = public_key.modpow(bob_private_key(), group().p());
+
+    let message = "crazy flamboyant for the rap enjoyment".as_bytes().to_vec();
+    let mac = hmac_sha256(&shared_secret.to_bytes_be(), &message)?;
+
+    Ok((message, mac))
+}
+
+/// Recovers the private key behind an oracle like [`bob`], by sending it elements of small order:
+/// the MAC then gives away the private key modulo each of those orders, which are combined with
+/// the CRT.
+fn recover_private_key<F>(group: &DhGroup, oracle: F) -> error::Result<BigUint>
+where
+    F: Fn(&BigUint) -> error::Result<(Vec<u8>, Vec<u8>)>,
+{
+    let (x, modulus) = recover_residue(group, oracle)?;
+    if modulus <= *group.q() {
+        return Err(CryptopalsError::AttackFailed(
+            "Not enough small factors to recover the whole key",
+        ));
+    }
+
+    Ok(x)
+}
+
+/// Recovers the private key behind an oracle like [`bob`] modulo the product of the small factors
+/// of `(p - 1) / q` (stopping once that exceeds `q`), returning it along with that product.
+pub fn recover_residue<F>(group: &DhGroup, oracle: F) -> error::Result<(BigUint, BigUint)>
+where
+    F: Fn(&BigUint) -> error::Result<(Vec<u8>, Vec<u8>)>,
+{
+    let j = (group.p() - 1u32) / group.q();
+    let (factors, _) = numtheory::trial_division(&j, 1 << 16);
+
+    let mut congruences = vec![];
+    let mut modulus = BigUint::one();
+    for (r, _) in factors {
+        if modulus > *group.q() {
+            break;
+        }
+
+        let r = BigUint::from(r);
+        let h = group.element_of_order(&r);
+        let (message, mac) = oracle(&h)?;
+
+        // the shared secret is h^x = h^(x mod r), so just try them all
+        let mut candidate = BigUint::one();
+        let mut residue = BigUint::ZERO;
+        while residue < r {
+            if hmac_sha256(&candidate.to_bytes_be(), &message)? == mac {
+                break;
+            }
+            candidate = candidate * &h % group.p();
+            residue += 1u32;
+        }
+
+        if residue == r {
+            return Err(CryptopalsError::AttackFailed(
+                "Oracle does not behave as expected",
+            ));
+        }
+
+        modulus *= &r;
+        congruences.push((residue, r));
+    }
+
+    Ok(numtheory::crt(&congruences).expect("Small factors are distinct primes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let private_key = recover_private_key(group(), bob).unwrap();
+
+        assert_eq!(bob_private_key(), &private_key);
+    }
+
+    #[test]
+    fn element_of_order_works() {
+        let r = BigUint::from(7963u32);
+        let h = group().element_of_order(&r);
+
+        assert!(!h.is_one());
+        assert!(h.modpow(&r, group().p()).is_one());
+    }
+}
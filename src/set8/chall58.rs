@@ -0,0 +1,190 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    numtheory::{self, Group, JumpParams},
+};
+
+use super::chall57::{self, DhGroup};
+
+pub struct Chall58;
+
+impl Challenge for Chall58 {
+    fn number(&self) -> u8 {
+        58
+    }
+
+    fn title(&self) -> &'static str {
+        "Pollard's Method for Catching Kangaroos"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall58()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Index of y in [0, 2^20]", 705485u64)
+            .with("Index of y in [0, 2^40]", 359579674340u64)
+            .with("Matches public key", true)
+    }
+}
+
+pub fn solve_chall58() -> error::Result<Output> {
+    let mut output = Output::new();
+    for (label, y, bits) in [
+        (
+            "Index of y in [0, 2^20]",
+            concat!(
+                "7760073848032689505395005705677365876654629189298052775754597607446617558600394",
+                "076764814236081991643094239886772481052254010323780165093955236429914607119"
+            ),
+            20,
+        ),
+        (
+            "Index of y in [0, 2^40]",
+            concat!(
+                "9388897478013399550694114614498790691034187453089355259602614074132918843899833",
+                "277397448144245883225611726912025846772975325932794909655215329941809013733"
+            ),
+            40,
+        ),
+    ] {
+        let y = BigUint::parse_bytes(y.as_bytes(), 10).expect("Invalid number");
+        let index =
+            discrete_log_in_interval(group(), group().g(), &y, &(BigUint::from(1u32) << bits))
+                .ok_or(CryptopalsError::AttackFailed("Failed to find discrete log"))?;
+        output.push(label, index);
+    }
+
+    let public_key = group().public_key(bob_private_key());
+    let queries = Cell::new(0_usize);
+    let private_key = recover_private_key(group(), &public_key, |h| {
+        queries.set(queries.get() + 1);
+        bob(h)
+    })?;
+
+    Ok(output
+        .with(
+            "Matches public key",
+            group().public_key(&private_key) == public_key,
+        )
+        .with("Bob's public key", public_key)
+        .with("Recovered private key", private_key)
+        .with("Queries", queries.get()))
+}
+
+static GROUP: OnceLock<DhGroup> = OnceLock::new();
+
+fn group() -> &'static DhGroup {
+    GROUP.get_or_init(|| {
+        DhGroup::from_decimal(
+            concat!(
+                "1147037487492527565811666350723216140208665025845389627453499167689899926264158",
+                "1519101074740642369848233294239851519212341844337347119899874391456329785623"
+            ),
+            concat!(
+                "6229523353339612969781592660847410858898813587384599399782901799360636355667402",
+                "58555167783009058567397963466103140082647486611657350811560630587013183357"
+            ),
+            "335062023296420808191071248367701059461",
+        )
+    })
+}
+
+static BOB_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn bob_private_key() -> &'static BigUint {
+    BOB_PRIVATE_KEY.get_or_init(|| group().random_private_key())
+}
+
+/// Same as in challenge 57, but in a group where `(p - 1) / q` has fewer small factors.
+fn bob(public_key: &BigUint) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    let shared_secret = public_key.modpow(bob_private_key(), group().p());
+
+    let message = "crazy flamboyant for the rap enjoyment".as_bytes().to_vec();
+    let mac = chall57::hmac_sha256(&shared_secret.to_bytes_be(), &message)?;
+
+    Ok((message, mac))
+}
+
+/// Finds the discrete log of `y` to the base `g`, knowing it is in `[0, width]`, with the kangaroo
+/// algorithm. Unlucky walks are retried with the tame kangaroo going further.
+fn discrete_log_in_interval<G: Group>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    width: &BigUint,
+) -> Option<BigUint> {
+    let mut params = JumpParams::for_width(width);
+
+    for _ in 0..4 {
+        if let Some(index) = numtheory::kangaroo(group, g, y, (&BigUint::zero(), width), params) {
+            return Some(index);
+        }
+        params.n *= 2;
+    }
+
+    None
+}
+
+/// Recovers the private key behind the given public key, first modulo the small factors of
+/// `(p - 1) / q` like in challenge 57, and then catching the rest with a kangaroo.
+fn recover_private_key<F>(
+    group: &DhGroup,
+    public_key: &BigUint,
+    oracle: F,
+) -> error::Result<BigUint>
+where
+    F: Fn(&BigUint) -> error::Result<(Vec<u8>, Vec<u8>)>,
+{
+    let (n, r) = chall57::recover_residue(group, oracle)?;
+
+    // x = n + m * r, so y * g^-n = (g^r)^m, with m in [0, (q - 1) / r]
+    let g = group.pow(group.g(), &r);
+    let y = group.op(public_key, &group.pow(group.g(), &(group.q() - &n)));
+    let width = (group.q() - 1u32) / &r;
+
+    match discrete_log_in_interval(group, &g, &y, &width) {
+        Some(m) => Ok(n + m * r),
+        None => Err(CryptopalsError::AttackFailed(
+            "Kangaroo did not catch the private key",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let public_key = group().public_key(bob_private_key());
+        let private_key = recover_private_key(group(), &public_key, bob).unwrap();
+
+        assert_eq!(bob_private_key(), &private_key);
+    }
+
+    #[test]
+    fn discrete_log_in_interval_works() {
+        let y = BigUint::parse_bytes(
+            concat!(
+                "7760073848032689505395005705677365876654629189298052775754597607446617558600394",
+                "076764814236081991643094239886772481052254010323780165093955236429914607119"
+            )
+            .as_bytes(),
+            10,
+        )
+        .unwrap();
+        let index =
+            discrete_log_in_interval(group(), group().g(), &y, &(BigUint::from(1u32) << 20))
+                .unwrap();
+
+        assert_eq!(y, group().public_key(&index));
+    }
+}
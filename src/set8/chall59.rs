@@ -0,0 +1,491 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    numtheory,
+};
+
+use super::chall57;
+
+pub struct Chall59;
+
+impl Challenge for Chall59 {
+    fn number(&self) -> u8 {
+        59
+    }
+
+    fn title(&self) -> &'static str {
+        "Elliptic Curve Diffie-Hellman and Invalid-Curve Attacks"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall59()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Matches public key", true)
+            .with("Validating oracle rejects the attack", true)
+    }
+}
+
+pub fn solve_chall59() -> error::Result<Output> {
+    let public_key = group().public_key(bob_private_key());
+
+    let queries = Cell::new(0_usize);
+    let private_key = recover_private_key(group(), &invalid_curves(), |h| {
+        queries.set(queries.get() + 1);
+        bob(h)
+    })?
+    .ok_or(CryptopalsError::AttackFailed("Oracle rejected our points"))?;
+
+    let rejected = recover_private_key(group(), &invalid_curves(), validating_bob)?.is_none();
+
+    Ok(Output::new()
+        .with("Bob's public key", format!("{public_key:?}"))
+        .with(
+            "Matches public key",
+            group().public_key(&private_key) == public_key,
+        )
+        .with("Recovered private key", private_key)
+        .with("Queries", queries.get())
+        .with("Validating oracle rejects the attack", rejected))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+impl Point {
+    /// Serializes the point as its coordinates (big-endian, each as long as the modulus).
+    pub fn to_bytes(&self, p: &BigUint) -> Vec<u8> {
+        let len = p.to_bytes_be().len();
+
+        match self {
+            Point::Infinity => vec![0; 2 * len],
+            Point::Affine(x, y) => [x, y]
+                .iter()
+                .flat_map(|c| {
+                    let bytes = c.to_bytes_be();
+                    let mut padded = vec![0; len - bytes.len()];
+                    padded.extend(bytes);
+                    padded
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Elliptic curve in short Weierstrass form: `y^2 = x^3 + ax + b`, over the integers modulo `p`.
+#[derive(Clone, Debug)]
+pub struct WeierstrassCurve {
+    a: BigUint,
+    b: BigUint,
+    p: BigUint,
+}
+
+impl WeierstrassCurve {
+    pub fn new(a: BigUint, b: BigUint, p: BigUint) -> Self {
+        Self {
+            a: a % &p,
+            b: b % &p,
+            p,
+        }
+    }
+
+    /// The same curve but with a different `b`, which doesn't play any role in the group law.
+    pub fn with_b(&self, b: BigUint) -> Self {
+        Self::new(self.a.clone(), b, self.p.clone())
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        (x + &self.p - y % &self.p) % &self.p
+    }
+
+    /// Returns `None` if `y` has no inverse modulo `p`.
+    fn div(&self, x: &BigUint, y: &BigUint) -> Option<BigUint> {
+        let inverse = numtheory::modinv(y, &self.p)?;
+
+        Some(x * inverse % &self.p)
+    }
+
+    fn rhs(&self, x: &BigUint) -> BigUint {
+        (x.modpow(&BigUint::from(3u32), &self.p) + &self.a * x + &self.b) % &self.p
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => *x < self.p && *y < self.p && y * y % &self.p == self.rhs(x),
+        }
+    }
+
+    /// The same point, with its coordinates reduced modulo `p`.
+    pub fn reduce(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x % &self.p, y % &self.p),
+        }
+    }
+
+    pub fn neg(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x.clone(), self.sub(&BigUint::zero(), y)),
+        }
+    }
+
+    /// Adds two points, or returns `None` if the slope can't be computed, which only happens when
+    /// the coordinates aren't reduced modulo `p` (or `p` isn't prime).
+    pub fn checked_add(&self, p1: &Point, p2: &Point) -> Option<Point> {
+        let ((x1, y1), (x2, y2)) = match (p1, p2) {
+            (Point::Infinity, _) => return Some(p2.clone()),
+            (_, Point::Infinity) => return Some(p1.clone()),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => ((x1, y1), (x2, y2)),
+        };
+
+        if *p1 == self.neg(p2) {
+            return Some(Point::Infinity);
+        }
+
+        let slope = if p1 == p2 {
+            self.div(&(BigUint::from(3u32) * x1 * x1 + &self.a), &(y1 << 1))?
+        } else {
+            self.div(&self.sub(y2, y1), &self.sub(x2, x1))?
+        };
+
+        let x3 = self.sub(&self.sub(&(&slope * &slope), x1), x2);
+        let y3 = self.sub(&(slope * self.sub(x1, &x3)), y1);
+
+        Some(Point::Affine(x3, y3))
+    }
+
+    /// Same as [`Self::checked_add`], for points known to have reduced coordinates.
+    pub fn add(&self, p1: &Point, p2: &Point) -> Point {
+        self.checked_add(p1, p2)
+            .expect("Points must have reduced coordinates")
+    }
+
+    pub fn double(&self, point: &Point) -> Point {
+        self.add(point, point)
+    }
+
+    /// Double-and-add scalar multiplication, or `None` if any of the additions fails (see
+    /// [`Self::checked_add`]).
+    pub fn checked_scalar_mul(&self, point: &Point, k: &BigUint) -> Option<Point> {
+        (0..k.bits()).rev().try_fold(Point::Infinity, |acc, i| {
+            let acc = self.checked_add(&acc, &acc)?;
+            if k.bit(i) {
+                self.checked_add(&acc, point)
+            } else {
+                Some(acc)
+            }
+        })
+    }
+
+    /// Same as [`Self::checked_scalar_mul`], for points known to have reduced coordinates.
+    pub fn scalar_mul(&self, point: &Point, k: &BigUint) -> Point {
+        self.checked_scalar_mul(point, k)
+            .expect("Points must have reduced coordinates")
+    }
+
+    /// Finds a random point on the curve (other than the point at infinity).
+    pub fn random_point(&self) -> Point {
+        loop {
+            let x = rand::thread_rng().gen_biguint_below(&self.p);
+            if let Some(y) = numtheory::sqrt_mod(&self.rhs(&x), &self.p) {
+                return Point::Affine(x, y);
+            }
+        }
+    }
+}
+
+impl numtheory::Group for WeierstrassCurve {
+    type Element = Point;
+
+    fn op(&self, a: &Point, b: &Point) -> Point {
+        self.add(a, b)
+    }
+
+    fn pow(&self, a: &Point, exponent: &BigUint) -> Point {
+        self.scalar_mul(a, exponent)
+    }
+
+    fn hash(&self, a: &Point) -> u64 {
+        match a {
+            Point::Infinity => 0,
+            Point::Affine(x, _) => x.iter_u64_digits().next().unwrap_or(0),
+        }
+    }
+}
+
+/// Elliptic curve group for ECDH: a base point on the curve, of (prime) order `q`.
+#[derive(Clone, Debug)]
+pub struct EcGroup {
+    curve: WeierstrassCurve,
+    base: Point,
+    q: BigUint,
+}
+
+impl EcGroup {
+    pub fn new(curve: WeierstrassCurve, base: Point, q: BigUint) -> Self {
+        Self { curve, base, q }
+    }
+
+    pub fn curve(&self) -> &WeierstrassCurve {
+        &self.curve
+    }
+
+    pub fn base(&self) -> &Point {
+        &self.base
+    }
+
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    pub fn random_private_key(&self) -> BigUint {
+        rand::thread_rng().gen_biguint_range(&BigUint::one(), &self.q)
+    }
+
+    pub fn public_key(&self, private_key: &BigUint) -> Point {
+        self.curve.scalar_mul(&self.base, private_key)
+    }
+
+    /// Returns `None` if the public key's coordinates aren't reduced modulo `p` (see
+    /// [`WeierstrassCurve::checked_add`]).
+    pub fn shared_secret(&self, private_key: &BigUint, public_key: &Point) -> Option<Point> {
+        self.curve.checked_scalar_mul(public_key, private_key)
+    }
+}
+
+fn parse(n: &str) -> BigUint {
+    BigUint::parse_bytes(n.as_bytes(), 10).expect("Invalid number")
+}
+
+static GROUP: OnceLock<EcGroup> = OnceLock::new();
+
+pub fn group() -> &'static EcGroup {
+    GROUP.get_or_init(|| {
+        let p = parse("233970423115425145524320034830162017933");
+        let a = &p - 95051u32;
+        let curve = WeierstrassCurve::new(a, BigUint::from(11279326u32), p);
+        let base = Point::Affine(
+            BigUint::from(182u32),
+            parse("85518893674295321206118380980485522083"),
+        );
+
+        EcGroup::new(curve, base, parse("29246302889428143187362802287225875743"))
+    })
+}
+
+/// Curves that only differ from ours in `b`, along with their orders.
+fn invalid_curves() -> Vec<(WeierstrassCurve, BigUint)> {
+    [
+        (210u32, "233970423115425145550826547352470124412"),
+        (504, "233970423115425145544350131142039591210"),
+        (727, "233970423115425145545378039958152057148"),
+    ]
+    .into_iter()
+    .map(|(b, order)| (group().curve().with_b(BigUint::from(b)), parse(order)))
+    .collect()
+}
+
+static BOB_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn bob_private_key() -> &'static BigUint {
+    BOB_PRIVATE_KEY.get_or_init(|| group().random_private_key())
+}
+
+fn bob_reply(public_key: &Point) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    let public_key = group().curve().reduce(public_key);
+    let shared_secret = group()
+        .shared_secret(bob_private_key(), &public_key)
+        .ok_or(CryptopalsError::InvalidParameter(
+            "Public key has no shared secret",
+        ))?;
+
+    let message = "crazy flamboyant for the rap enjoyment".as_bytes().to_vec();
+    let mac = chall57::hmac_sha256(&shared_secret.to_bytes(group().curve().p()), &message)?;
+
+    Ok((message, mac))
+}
+
+/// Bob's side of ECDH: given someone's public key, replies with a message and its MAC, keyed by
+/// the shared secret. He doesn't check that the point is actually on the curve.
+fn bob(public_key: &Point) -> error::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    bob_reply(public_key).map(Some)
+}
+
+/// Same as [`bob`], but rejecting any public key that is not a point of the right order on the
+/// curve.
+fn validating_bob(public_key: &Point) -> error::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let curve = group().curve();
+    if *public_key == Point::Infinity
+        || !curve.contains(public_key)
+        || curve.scalar_mul(public_key, group().q()) != Point::Infinity
+    {
+        return Ok(None);
+    }
+
+    bob_reply(public_key).map(Some)
+}
+
+/// Recovers the private key behind an oracle like [`bob`] by sending it points of small order on
+/// other curves (with the same `a`, so the oracle does the same computations): the MAC then gives
+/// away the private key modulo each of those orders, which are combined with the CRT.
+///
+/// Returns `None` if the oracle rejects any of the points.
+fn recover_private_key<F>(
+    group: &EcGroup,
+    invalid_curves: &[(WeierstrassCurve, BigUint)],
+    oracle: F,
+) -> error::Result<Option<BigUint>>
+where
+    F: Fn(&Point) -> error::Result<Option<(Vec<u8>, Vec<u8>)>>,
+{
+    let mut congruences: Vec<(BigUint, BigUint)> = vec![];
+    let mut modulus = BigUint::one();
+
+    for (curve, order) in invalid_curves {
+        let (factors, _) = numtheory::trial_division(order, 1 << 16);
+
+        for (r, exponent) in factors {
+            let r = BigUint::from(r);
+            if modulus > *group.q() || congruences.iter().any(|(_, m)| *m == r) {
+                continue;
+            }
+
+            let h = loop {
+                // the curve group isn't necessarily cyclic, so get rid of the rest of the order
+                // and then of any extra powers of r one by one
+                let mut h = curve.scalar_mul(&curve.random_point(), &(order / r.pow(exponent)));
+                let mut next = curve.scalar_mul(&h, &r);
+                while next != Point::Infinity {
+                    h = next;
+                    next = curve.scalar_mul(&h, &r);
+                }
+
+                if h != Point::Infinity {
+                    break h;
+                }
+            };
+
+            let Some((message, mac)) = oracle(&h)? else {
+                return Ok(None);
+            };
+
+            // the shared secret is x * h = (x mod r) * h, so just try them all
+            let mut candidate = Point::Infinity;
+            let mut residue = BigUint::zero();
+            while residue < r {
+                if chall57::hmac_sha256(&candidate.to_bytes(curve.p()), &message)? == mac {
+                    break;
+                }
+                candidate = curve.add(&candidate, &h);
+                residue += 1u32;
+            }
+
+            if residue == r {
+                return Err(CryptopalsError::AttackFailed(
+                    "Oracle does not behave as expected",
+                ));
+            }
+
+            modulus *= &r;
+            congruences.push((residue, r));
+        }
+    }
+
+    if modulus <= *group.q() {
+        return Err(CryptopalsError::AttackFailed(
+            "Not enough small factors to recover the whole key",
+        ));
+    }
+
+    let (x, _) = numtheory::crt(&congruences).expect("Small factors are distinct primes");
+
+    Ok(Some(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let private_key = recover_private_key(group(), &invalid_curves(), bob)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(bob_private_key(), &private_key);
+    }
+
+    #[test]
+    fn validating_oracle_resists() {
+        assert_eq!(
+            None,
+            recover_private_key(group(), &invalid_curves(), validating_bob).unwrap()
+        );
+
+        // but still works for honest peers
+        let alice_private_key = group().random_private_key();
+        let alice_public_key = group().public_key(&alice_private_key);
+        assert!(validating_bob(&alice_public_key).unwrap().is_some());
+    }
+
+    #[test]
+    fn ecdh_works() {
+        let curve = group().curve();
+        let base = group().base();
+        assert!(curve.contains(base));
+        assert_eq!(Point::Infinity, curve.scalar_mul(base, group().q()));
+        assert_eq!(*base, group().public_key(&BigUint::one()));
+
+        let (a, b) = (group().random_private_key(), group().random_private_key());
+        assert_eq!(
+            group().shared_secret(&a, &group().public_key(&b)),
+            group().shared_secret(&b, &group().public_key(&a))
+        );
+    }
+
+    #[test]
+    fn bob_reduces_coordinates() {
+        let p = group().curve().p();
+        let Point::Affine(x, y) = group().public_key(&group().random_private_key()) else {
+            unreachable!("Private keys are below the order of the base point");
+        };
+
+        let reduced = bob(&Point::Affine(x.clone(), y.clone())).unwrap();
+        assert_eq!(reduced, bob(&Point::Affine(&x + p, y.clone())).unwrap());
+        assert_eq!(reduced, bob(&Point::Affine(x, y + p)).unwrap());
+
+        // a point with coordinate p is the same as one with coordinate 0
+        assert!(bob(&Point::Affine(p.clone(), BigUint::zero())).is_ok());
+        assert!(bob(&Point::Affine(BigUint::zero(), p.clone())).is_ok());
+    }
+
+    #[test]
+    fn checked_add_rejects_unreduced_coordinates() {
+        let curve = group().curve();
+        let Point::Affine(x, y) = group().base().clone() else {
+            unreachable!("The base point is affine");
+        };
+
+        assert_eq!(
+            None,
+            curve.checked_add(group().base(), &Point::Affine(x + curve.p(), y))
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    numtheory::{self, Group, JumpParams},
+};
+
+use super::{
+    chall57,
+    chall59::{Point, WeierstrassCurve},
+};
+
+const SLOW: bool = true;
+
+pub struct Chall60;
+
+impl Challenge for Chall60 {
+    fn number(&self) -> u8 {
+        60
+    }
+
+    fn title(&self) -> &'static str {
+        "Single-Coordinate Ladders and Insecure Twists"
+    }
+
+    fn slow(&self) -> bool {
+        SLOW
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall60()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches public key", true)
+    }
+}
+
+pub fn solve_chall60() -> error::Result<Output> {
+    let public_key = bob_public_key();
+    let queries = Cell::new(0_usize);
+    let private_key = recover_private_key(curve(), &public_key, |u| {
+        queries.set(queries.get() + 1);
+        bob(u)
+    })?;
+
+    Ok(Output::new()
+        .with(
+            "Matches public key",
+            curve().ladder(&base_u(), &private_key) == public_key,
+        )
+        .with("Bob's public key", public_key)
+        .with("Recovered private key (up to sign)", private_key)
+        .with("Queries", queries.get()))
+}
+
+/// Montgomery curve: `v^2 = u^3 + Au^2 + u`, over the integers modulo `p`.
+#[derive(Clone, Debug)]
+pub struct MontgomeryCurve {
+    a: BigUint,
+    p: BigUint,
+}
+
+impl MontgomeryCurve {
+    pub fn new(a: BigUint, p: BigUint) -> Self {
+        Self { a: a % &p, p }
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        (x + &self.p - y % &self.p) % &self.p
+    }
+
+    fn div(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        // dividing by 0 gives 0, like with y^(p-2), which is where the point at infinity comes from
+        match numtheory::modinv(y, &self.p) {
+            Some(inverse) => x * inverse % &self.p,
+            None => BigUint::zero(),
+        }
+    }
+
+    fn rhs(&self, u: &BigUint) -> BigUint {
+        (u * u * u + &self.a * u * u + u) % &self.p
+    }
+
+    /// Whether there is a point with this u-coordinate on the curve (if not, there is one on its
+    /// quadratic twist instead).
+    pub fn contains(&self, u: &BigUint) -> bool {
+        numtheory::sqrt_mod(&self.rhs(u), &self.p).is_some()
+    }
+
+    /// Montgomery ladder: u-coordinate of `k` times the point with the given u-coordinate (on the
+    /// curve or on its twist), without ever needing v. The point at infinity comes out as 0.
+    pub fn ladder(&self, u: &BigUint, k: &BigUint) -> BigUint {
+        let p = &self.p;
+        let (mut u2, mut w2) = (BigUint::one(), BigUint::zero());
+        let (mut u3, mut w3) = (u.clone(), BigUint::one());
+
+        for i in (0..k.bits()).rev() {
+            if k.bit(i) {
+                (u2, u3, w2, w3) = (u3, u2, w3, w2);
+            }
+
+            let u3_next = self.sub(&(&u2 * &u3), &(&w2 * &w3)).pow(2) % p;
+            let w3_next = u * self.sub(&(&u2 * &w3), &(&w2 * &u3)).pow(2) % p;
+            let u2_next = self.sub(&(&u2 * &u2), &(&w2 * &w2)).pow(2) % p;
+            let w2_next =
+                4u32 * &u2 * &w2 * ((&u2 * &u2 + &self.a * &u2 * &w2 + &w2 * &w2) % p) % p;
+            (u2, w2, u3, w3) = (u2_next, w2_next, u3_next, w3_next);
+
+            if k.bit(i) {
+                (u2, u3, w2, w3) = (u3, u2, w3, w2);
+            }
+        }
+
+        self.div(&u2, &w2)
+    }
+
+    /// Differential addition: u-coordinate of `P + Q`, given those of `P`, `Q` and `P - Q`.
+    pub fn differential_add(&self, u_p: &BigUint, u_q: &BigUint, u_diff: &BigUint) -> BigUint {
+        let numerator = self.sub(&(u_p * u_q), &BigUint::one()).pow(2);
+        let denominator = u_diff * self.sub(u_p, u_q).pow(2);
+
+        self.div(&(numerator % &self.p), &(denominator % &self.p))
+    }
+
+    /// `A / 3`, the offset between u-coordinates and the x-coordinates of the Weierstrass form.
+    fn offset(&self) -> BigUint {
+        self.div(&self.a, &BigUint::from(3u32))
+    }
+
+    /// The equivalent curve in short Weierstrass form.
+    pub fn to_weierstrass(&self) -> WeierstrassCurve {
+        let a2 = &self.a * &self.a % &self.p;
+        let a = self.div(&self.sub(&BigUint::from(3u32), &a2), &BigUint::from(3u32));
+        let b = self.div(
+            &self.sub(&(2u32 * &a2 * &self.a), &(9u32 * &self.a)),
+            &BigUint::from(27u32),
+        );
+
+        WeierstrassCurve::new(a, b, self.p.clone())
+    }
+
+    /// Maps a point `(u, v)` on this curve to the Weierstrass form.
+    pub fn to_weierstrass_point(&self, u: &BigUint, v: &BigUint) -> Point {
+        Point::Affine((u + self.offset()) % &self.p, v.clone())
+    }
+
+    /// Maps a point on the Weierstrass form back to `(u, v)` on this curve.
+    pub fn to_montgomery_point(&self, point: &Point) -> Option<(BigUint, BigUint)> {
+        match point {
+            Point::Infinity => None,
+            Point::Affine(x, y) => Some((self.sub(x, &self.offset()), y.clone())),
+        }
+    }
+
+    /// Finds a random u-coordinate on the curve's quadratic twist.
+    pub fn random_twist_u(&self) -> BigUint {
+        loop {
+            let u = rand::thread_rng().gen_biguint_below(&self.p);
+            if !self.contains(&u) {
+                return u;
+            }
+        }
+    }
+}
+
+fn parse(n: &str) -> BigUint {
+    BigUint::parse_bytes(n.as_bytes(), 10).expect("Invalid number")
+}
+
+static CURVE: OnceLock<MontgomeryCurve> = OnceLock::new();
+
+fn curve() -> &'static MontgomeryCurve {
+    CURVE.get_or_init(|| {
+        MontgomeryCurve::new(
+            BigUint::from(534u32),
+            parse("233970423115425145524320034830162017933"),
+        )
+    })
+}
+
+fn base_u() -> BigUint {
+    BigUint::from(4u32)
+}
+
+/// Order of the base point.
+fn q() -> BigUint {
+    parse("29246302889428143187362802287225875743")
+}
+
+/// Order of the whole curve (the base point's order times the cofactor).
+fn curve_order() -> BigUint {
+    parse("233970423115425145498902418297807005944")
+}
+
+fn u_bytes(u: &BigUint, p: &BigUint) -> Vec<u8> {
+    let bytes = u.to_bytes_be();
+    let mut padded = vec![0; p.to_bytes_be().len() - bytes.len()];
+    padded.extend(bytes);
+
+    padded
+}
+
+static BOB_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn bob_private_key() -> &'static BigUint {
+    BOB_PRIVATE_KEY.get_or_init(|| rand::thread_rng().gen_biguint_range(&BigUint::one(), &q()))
+}
+
+fn bob_public_key() -> BigUint {
+    curve().ladder(&base_u(), bob_private_key())
+}
+
+/// Bob's side of x-only ECDH: given someone's public u-coordinate, replies with a message and its
+/// MAC, keyed by the shared u-coordinate. He doesn't check that it's actually on the curve.
+fn bob(public_key: &BigUint) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    let shared_secret = curve().ladder(public_key, bob_private_key());
+
+    let message = "crazy flamboyant for the rap enjoyment".as_bytes().to_vec();
+    let mac = chall57::hmac_sha256(&u_bytes(&shared_secret, curve().p()), &message)?;
+
+    Ok((message, mac))
+}
+
+/// Finds a u-coordinate on the twist whose order is exactly the product of the given primes, each
+/// of which must divide the twist's order exactly once.
+fn twist_point_of_order(curve: &MontgomeryCurve, twist_order: &BigUint, primes: &[u64]) -> BigUint {
+    let order: BigUint = primes.iter().product();
+
+    loop {
+        let u = curve.ladder(&curve.random_twist_u(), &(twist_order / &order));
+        if primes
+            .iter()
+            .all(|&r| !curve.ladder(&u, &(&order / r)).is_zero())
+        {
+            return u;
+        }
+    }
+}
+
+/// Recovers the private key behind an x-only oracle like [`bob`] modulo `r`, up to sign, given a
+/// point of order `r` and the oracle's reply to it.
+fn brute_force_residue(
+    curve: &MontgomeryCurve,
+    u: &BigUint,
+    r: u64,
+    (message, mac): &(Vec<u8>, Vec<u8>),
+) -> error::Result<u64> {
+    let matches = |candidate: &BigUint| -> error::Result<bool> {
+        Ok(chall57::hmac_sha256(&u_bytes(candidate, curve.p()), message)? == *mac)
+    };
+
+    if matches(&BigUint::zero())? {
+        return Ok(0);
+    }
+
+    // k and -k give the same u-coordinate, so only half of them need to be tried, and the next
+    // one always comes from a differential addition with the previous two
+    let (mut previous, mut current) = (u.clone(), curve.ladder(u, &BigUint::from(2u32)));
+    if matches(u)? {
+        return Ok(1);
+    }
+    for k in 2..=r / 2 {
+        if matches(&current)? {
+            return Ok(k);
+        }
+        (previous, current) = (
+            current.clone(),
+            curve.differential_add(&current, u, &previous),
+        );
+    }
+
+    Err(CryptopalsError::AttackFailed(
+        "Oracle does not behave as expected",
+    ))
+}
+
+/// Recovers the private key behind the given public key (up to sign, as that's all that matters
+/// for x-only ECDH) by sending the oracle points of small order on the curve's twist, and then
+/// catching the rest with a kangaroo on the Weierstrass form.
+fn recover_private_key<F>(
+    curve: &MontgomeryCurve,
+    public_key: &BigUint,
+    oracle: F,
+) -> error::Result<BigUint>
+where
+    F: Fn(&BigUint) -> error::Result<(Vec<u8>, Vec<u8>)>,
+{
+    let twist_order = 2u32 * (curve.p() + 1u32) - curve_order();
+    let (factors, _) = numtheory::trial_division(&twist_order, 1 << 22);
+    let primes: Vec<u64> = factors
+        .into_iter()
+        .filter(|&(r, exponent)| r > 2 && exponent == 1)
+        .map(|(r, _)| r)
+        .collect();
+
+    // residues of the private key, each only known up to sign
+    let mut residues = vec![];
+    for &r in &primes {
+        let u = twist_point_of_order(curve, &twist_order, &[r]);
+        residues.push(brute_force_residue(curve, &u, r, &oracle(&u)?)?);
+    }
+
+    // fix the signs relative to an anchor, by checking which combination of the two works on a
+    // point whose order is the product of both
+    let Some(anchor) = residues.iter().position(|&n| n != 0) else {
+        return Err(CryptopalsError::AttackFailed(
+            "Private key is a multiple of all the small factors",
+        ));
+    };
+    let (r_anchor, n_anchor) = (primes[anchor], residues[anchor]);
+    for i in 0..primes.len() {
+        if i == anchor || residues[i] == 0 {
+            continue;
+        }
+
+        let (r, n) = (primes[i], residues[i]);
+        let u = twist_point_of_order(curve, &twist_order, &[r_anchor, r]);
+        let (message, mac) = oracle(&u)?;
+
+        let (combined, _) = numtheory::crt(&[
+            (BigUint::from(n_anchor), BigUint::from(r_anchor)),
+            (BigUint::from(n), BigUint::from(r)),
+        ])
+        .expect("Small factors are distinct primes");
+        let shared_secret = curve.ladder(&u, &combined);
+        if chall57::hmac_sha256(&u_bytes(&shared_secret, curve.p()), &message)? != mac {
+            residues[i] = r - n;
+        }
+    }
+
+    let congruences: Vec<_> = primes
+        .iter()
+        .zip(&residues)
+        .map(|(&r, &n)| (BigUint::from(n), BigUint::from(r)))
+        .collect();
+    let (n, r) = numtheory::crt(&congruences).expect("Small factors are distinct primes");
+
+    // the private key is now +-n + m * r, so whichever of x and -x is n modulo r is n + m * r, with
+    // m in [-w, w] for w = q / r + 1. That's a kangaroo on the Weierstrass form (where points can be
+    // added), after lifting the public key to a point, which is only known up to sign as well
+    let weierstrass = curve.to_weierstrass();
+    let lift = |u: &BigUint| {
+        let v = numtheory::sqrt_mod(&curve.rhs(u), curve.p()).expect("Point is on the curve");
+        curve.to_weierstrass_point(u, &v)
+    };
+    let (g, y) = (lift(&base_u()), lift(public_key));
+
+    let w = q() / &r + 1u32;
+    let jump_base = weierstrass.pow(&g, &r);
+    let shift = weierstrass.op(
+        &weierstrass.neg(&weierstrass.pow(&g, &n)),
+        &weierstrass.pow(&jump_base, &w),
+    );
+    let mut params = JumpParams::for_width(&(2u32 * &w));
+    for _ in 0..3 {
+        for y in [y.clone(), weierstrass.neg(&y)] {
+            let target = weierstrass.op(&y, &shift);
+            let found = numtheory::kangaroo(
+                &weierstrass,
+                &jump_base,
+                &target,
+                (&BigUint::zero(), &(2u32 * &w)),
+                params,
+            );
+
+            if let Some(m) = found {
+                return Ok((&n + m * &r + 2u32 * q() - &w * &r) % q());
+            }
+        }
+        params.n *= 2;
+    }
+
+    Err(CryptopalsError::AttackFailed(
+        "Kangaroo did not catch the private key",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let private_key = recover_private_key(curve(), &bob_public_key(), bob).unwrap();
+
+        assert!([bob_private_key().clone(), q() - bob_private_key()].contains(&private_key));
+    }
+
+    #[test]
+    fn montgomery_matches_weierstrass() {
+        let weierstrass = curve().to_weierstrass();
+        let u = base_u();
+        let v = numtheory::sqrt_mod(&curve().rhs(&u), curve().p()).unwrap();
+        let base = curve().to_weierstrass_point(&u, &v);
+
+        assert_eq!(Point::Affine(BigUint::from(182u32), v.clone()), base);
+        assert!(weierstrass.contains(&base));
+        assert!(curve().ladder(&u, &q()).is_zero());
+
+        let k = rand::thread_rng().gen_biguint_below(&q());
+        let (u_k, _) = curve()
+            .to_montgomery_point(&weierstrass.scalar_mul(&base, &k))
+            .unwrap();
+        assert_eq!(curve().ladder(&u, &k), u_k);
+        // scalars can be larger than p too
+        let large = &k + q() * 1000u32;
+        assert!(large.bits() > curve().p().bits());
+        assert_eq!(curve().ladder(&u, &large), u_k);
+
+        let (u_2, u_3) = (
+            curve().ladder(&u, &BigUint::from(2u32)),
+            curve().ladder(&u, &BigUint::from(3u32)),
+        );
+        assert_eq!(u_3, curve().differential_add(&u_2, &u, &u));
+    }
+}
@@ -0,0 +1,436 @@
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use openssl::sha::sha256;
+use rand::Rng;
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    numtheory::{self, ModP},
+};
+
+use super::chall59::{self, EcGroup, Point};
+
+pub struct Chall61;
+
+impl Challenge for Chall61 {
+    fn number(&self) -> u8 {
+        61
+    }
+
+    fn title(&self) -> &'static str {
+        "Duplicate-Signature Key Selection in ECDSA (and RSA)"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall61()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("ECDSA signature verifies under Alice's key", true)
+            .with("ECDSA signature verifies under our key", true)
+            .with("RSA signature verifies under Alice's key", true)
+            .with("RSA signature verifies our message under our key", true)
+    }
+}
+
+pub fn solve_chall61() -> error::Result<Output> {
+    let mut output = Output::new();
+    let message = "Pay Bob 1000 dollars".as_bytes();
+
+    let group = chall59::group();
+    let private_key = group.random_private_key();
+    let public_key = group.public_key(&private_key);
+    let signature = ecdsa_sign(group, &private_key, message);
+    output.push(
+        "ECDSA signature verifies under Alice's key",
+        ecdsa_verify(group, &public_key, message, &signature),
+    );
+
+    let (forged_group, forged_private_key) =
+        forge_ecdsa_key(group, &public_key, message, &signature)?;
+    let forged_public_key = forged_group.public_key(&forged_private_key);
+    output.push(
+        "ECDSA signature verifies under our key",
+        ecdsa_verify(&forged_group, &forged_public_key, message, &signature),
+    );
+
+    let key = RsaKeyPair::generate(1024);
+    let signature = key.sign(message)?;
+    output.push(
+        "RSA signature verifies under Alice's key",
+        rsa_verify(key.n(), key.e(), message, &signature)?,
+    );
+
+    let our_message = "Pay Eve 1000000 dollars".as_bytes();
+    let forged_key = forge_rsa_key(key.n(), our_message, &signature)?;
+    Ok(output.with(
+        "RSA signature verifies our message under our key",
+        rsa_verify(forged_key.n(), forged_key.e(), our_message, &signature)?,
+    ))
+}
+
+/// Hashes a message to a scalar for ECDSA: the leftmost bits of its SHA-256, as many as `q` has.
+pub fn hash_to_scalar(message: &[u8], q: &BigUint) -> BigUint {
+    let hash = BigUint::from_bytes_be(&sha256(message));
+    let excess = 256u64.saturating_sub(q.bits());
+
+    hash >> excess
+}
+
+/// Signs a message with ECDSA, with a random nonce, returning the signature as `(r, s)`.
+pub fn ecdsa_sign(group: &EcGroup, private_key: &BigUint, message: &[u8]) -> (BigUint, BigUint) {
+    loop {
+        let k = group.random_private_key();
+        if let Some(signature) = ecdsa_sign_with_nonce(group, private_key, message, &k) {
+            return signature;
+        }
+    }
+}
+
+/// Signs a message with ECDSA, with the given nonce (which must never be reused).
+///
+/// Returns `None` in the unlikely case that this nonce gives a degenerate signature.
+pub fn ecdsa_sign_with_nonce(
+    group: &EcGroup,
+    private_key: &BigUint,
+    message: &[u8],
+    k: &BigUint,
+) -> Option<(BigUint, BigUint)> {
+    let q = group.q();
+
+    let Point::Affine(x, _) = group.public_key(k) else {
+        return None;
+    };
+    let r = x % q;
+    let s = numtheory::modinv(k, q)? * (hash_to_scalar(message, q) + private_key * &r) % q;
+
+    (!r.is_zero() && !s.is_zero()).then_some((r, s))
+}
+
+/// Verifies an ECDSA signature `(r, s)` on a message.
+pub fn ecdsa_verify(
+    group: &EcGroup,
+    public_key: &Point,
+    message: &[u8],
+    (r, s): &(BigUint, BigUint),
+) -> bool {
+    let q = group.q();
+    if r.is_zero() || r >= q || s.is_zero() || s >= q {
+        return false;
+    }
+
+    let Some(s_inverse) = numtheory::modinv(s, q) else {
+        return false;
+    };
+    let (u1, u2) = (
+        hash_to_scalar(message, q) * &s_inverse % q,
+        r * s_inverse % q,
+    );
+    let curve = group.curve();
+    let point = curve.add(
+        &curve.scalar_mul(group.base(), &u1),
+        &curve.scalar_mul(public_key, &u2),
+    );
+
+    match point {
+        Point::Infinity => false,
+        Point::Affine(x, _) => x % q == *r,
+    }
+}
+
+/// Duplicate-signature key selection: given someone's signature on a message, makes up a group
+/// (same curve, but another base point) and a private key in it, under which the signature is
+/// just as valid.
+///
+/// Fails if `s` isn't invertible modulo `q`, as no signature that verifies can have such an `s`.
+pub fn forge_ecdsa_key(
+    group: &EcGroup,
+    public_key: &Point,
+    message: &[u8],
+    signature: &(BigUint, BigUint),
+) -> error::Result<(EcGroup, BigUint)> {
+    let (q, curve) = (group.q(), group.curve());
+    let s_inverse = numtheory::modinv(&signature.1, q).ok_or(CryptopalsError::InvalidParameter(
+        "Signature's s must be invertible modulo q",
+    ))?;
+    let u1 = hash_to_scalar(message, q) * &s_inverse % q;
+    let u2 = &signature.0 * s_inverse % q;
+
+    // verification computes u1 * G + u2 * Q = R, so with G' = R / t and Q' = d' * G', it computes
+    // (u1 + u2 * d') / t * R instead, which is R again for t = u1 + u2 * d'
+    let point = curve.add(
+        &curve.scalar_mul(group.base(), &u1),
+        &curve.scalar_mul(public_key, &u2),
+    );
+    loop {
+        let private_key = group.random_private_key();
+        let Some(t_inverse) = numtheory::modinv(&((&u1 + &u2 * &private_key) % q), q) else {
+            continue;
+        };
+
+        let base = curve.scalar_mul(&point, &t_inverse);
+        return Ok((EcGroup::new(curve.clone(), base, q.clone()), private_key));
+    }
+}
+
+/// RSA key pair, for textbook signatures with PKCS#1 v1.5 padding.
+pub struct RsaKeyPair {
+    n: BigUint,
+    e: BigUint,
+    d: BigUint,
+}
+
+impl RsaKeyPair {
+    pub fn new(n: BigUint, e: BigUint, d: BigUint) -> Self {
+        Self { n, e, d }
+    }
+
+    /// Generates a key pair with a modulus of exactly the given size, and `e = 65537`.
+    pub fn generate(bits: u64) -> Self {
+        let e = BigUint::from(65537u32);
+
+        loop {
+            let p = numtheory::random_prime(bits / 2);
+            let q = numtheory::random_prime(bits - bits / 2);
+            let n = &p * &q;
+            if n.bits() != bits {
+                continue;
+            }
+
+            let totient = (p - 1u32) * (q - 1u32);
+            if let Some(d) = numtheory::modinv(&e, &totient) {
+                return Self::new(n, e, d);
+            }
+        }
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    pub fn sign(&self, message: &[u8]) -> error::Result<BigUint> {
+        Ok(pkcs1_pad(message, &self.n)?.modpow(&self.d, &self.n))
+    }
+}
+
+/// ASN.1 `DigestInfo` prefix for SHA-256, as per RFC 8017.
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// Encodes a message to be signed as per PKCS#1 v1.5: `00 01 ff ... ff 00 DigestInfo hash`, as long
+/// as the modulus. That has to leave room for at least 8 bytes of `ff`, i.e. be 62 bytes or more.
+fn pkcs1_pad(message: &[u8], n: &BigUint) -> error::Result<BigUint> {
+    let len = n.to_bytes_be().len();
+    let ff_len = len
+        .checked_sub(3 + SHA256_DIGEST_INFO.len() + 32)
+        .filter(|&ff_len| ff_len >= 8)
+        .ok_or(CryptopalsError::InvalidParameter(
+            "Modulus too small for PKCS#1 v1.5 with SHA-256",
+        ))?;
+
+    let mut padded = vec![0x00, 0x01];
+    padded.extend(std::iter::repeat_n(0xff, ff_len));
+    padded.push(0x00);
+    padded.extend(SHA256_DIGEST_INFO);
+    padded.extend(sha256(message));
+
+    Ok(BigUint::from_bytes_be(&padded))
+}
+
+pub fn rsa_verify(
+    n: &BigUint,
+    e: &BigUint,
+    message: &[u8],
+    signature: &BigUint,
+) -> error::Result<bool> {
+    Ok(signature < n && signature.modpow(e, n) == pkcs1_pad(message, n)?)
+}
+
+/// Finds a prime `p` with at least the given number of bits, such that `p - 1` is the product of 2
+/// and distinct primes between `2^10` and `2^14` (none of which in `avoid`), which are returned
+/// along with it.
+fn smooth_prime(bits: u64, avoid: &[u64]) -> (BigUint, Vec<u64>) {
+    let mut rng = rand::thread_rng();
+
+    loop {
+        let mut factors = vec![2];
+        let mut product = BigUint::from(2u32);
+        while product.bits() < bits {
+            let r = rng.gen_range(1 << 10..1 << 14);
+            if !factors.contains(&r)
+                && !avoid.contains(&r)
+                && numtheory::is_probable_prime(&BigUint::from(r), 16)
+            {
+                factors.push(r);
+                product *= r;
+            }
+        }
+
+        let p = product + 1u32;
+        if numtheory::is_probable_prime(&p, 32) {
+            return (p, factors);
+        }
+    }
+}
+
+/// Whether `g` generates the whole multiplicative group modulo `p`, given the prime factors of
+/// `p - 1`.
+fn is_generator(g: &BigUint, p: &BigUint, factors: &[u64]) -> bool {
+    let order = p - 1u32;
+
+    !(g % p).is_zero()
+        && factors
+            .iter()
+            .all(|&r| !g.modpow(&(&order / r), p).is_one())
+}
+
+/// Duplicate-signature key selection for RSA: given someone's signature (under a modulus `n`),
+/// makes up a key pair under which it is a valid signature for a message of our choosing.
+///
+/// The new modulus has smooth `p - 1` and `q - 1`, so that `e` can be found by solving
+/// `s^e = pad(m)` modulo `p` and `q` with Pohlig-Hellman.
+pub fn forge_rsa_key(
+    n: &BigUint,
+    message: &[u8],
+    signature: &BigUint,
+) -> error::Result<RsaKeyPair> {
+    let bits = n.bits() / 2 + 1;
+
+    loop {
+        let (p, p_factors) = smooth_prime(bits, &[]);
+        if !is_generator(signature, &p, &p_factors) {
+            continue;
+        }
+        let (q, q_factors) = smooth_prime(bits, &p_factors);
+        if !is_generator(signature, &q, &q_factors) {
+            continue;
+        }
+
+        let modulus = &p * &q;
+        let padded = pkcs1_pad(message, &modulus)?;
+        let log = |p: &BigUint, factors: &[u64]| {
+            let group = ModP(p.clone());
+            numtheory::pohlig_hellman(&group, &(signature % p), &(&padded % p), factors)
+                .expect("Signature generates the whole group")
+        };
+        let (e_p, e_q) = (log(&p, &p_factors), log(&q, &q_factors));
+
+        // e = e_p (mod p - 1) and e = e_q (mod q - 1), which is only possible if they agree on the
+        // only factor in common
+        if e_p.is_odd() != e_q.is_odd() {
+            continue;
+        }
+        let congruences: Vec<_> = p_factors
+            .iter()
+            .map(|&r| (&e_p % r, BigUint::from(r)))
+            .chain(q_factors[1..].iter().map(|&r| (&e_q % r, BigUint::from(r))))
+            .collect();
+        let (e, lambda) = numtheory::crt(&congruences).expect("Factors are distinct primes");
+
+        if let Some(d) = numtheory::modinv(&e, &lambda) {
+            return Ok(RsaKeyPair::new(modulus, e, d));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let message = "Pay Bob 1000 dollars".as_bytes();
+
+        let group = chall59::group();
+        let private_key = group.random_private_key();
+        let signature = ecdsa_sign(group, &private_key, message);
+        let (forged_group, forged_private_key) =
+            forge_ecdsa_key(group, &group.public_key(&private_key), message, &signature).unwrap();
+        assert_ne!(group.base(), forged_group.base());
+        assert!(ecdsa_verify(
+            &forged_group,
+            &forged_group.public_key(&forged_private_key),
+            message,
+            &signature
+        ));
+
+        let key = RsaKeyPair::generate(512);
+        let signature = key.sign(message).unwrap();
+        let our_message = "Pay Eve 1000000 dollars".as_bytes();
+        let forged_key = forge_rsa_key(key.n(), our_message, &signature).unwrap();
+        assert!(rsa_verify(forged_key.n(), forged_key.e(), our_message, &signature).unwrap());
+        // the forged key is a real key pair, that can sign other messages too
+        let other_signature = forged_key.sign(message).unwrap();
+        assert!(rsa_verify(forged_key.n(), forged_key.e(), message, &other_signature).unwrap());
+    }
+
+    #[test]
+    fn ecdsa_works() {
+        let group = chall59::group();
+        let private_key = group.random_private_key();
+        let public_key = group.public_key(&private_key);
+        let signature = ecdsa_sign(group, &private_key, b"hello");
+
+        assert!(ecdsa_verify(group, &public_key, b"hello", &signature));
+        assert!(!ecdsa_verify(group, &public_key, b"hellp", &signature));
+        assert!(!ecdsa_verify(
+            group,
+            &group.public_key(&(private_key + 1u32)),
+            b"hello",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn forge_ecdsa_key_rejects_non_invertible_s() {
+        let group = chall59::group();
+        let public_key = group.public_key(&group.random_private_key());
+
+        for s in [BigUint::zero(), group.q().clone()] {
+            assert!(matches!(
+                forge_ecdsa_key(group, &public_key, b"hello", &(BigUint::one(), s)),
+                Err(CryptopalsError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rsa_works() {
+        let key = RsaKeyPair::generate(512);
+        let signature = key.sign(b"hello").unwrap();
+
+        assert_eq!(512, key.n().bits());
+        assert!(rsa_verify(key.n(), key.e(), b"hello", &signature).unwrap());
+        assert!(!rsa_verify(key.n(), key.e(), b"hellp", &signature).unwrap());
+    }
+
+    #[test]
+    fn pkcs1_pad_needs_room_for_padding() {
+        // 61 bytes leave room for only 7 bytes of ff, and 52 for none at all
+        for len in [61, 52, 40] {
+            let n = BigUint::one() << (8 * len - 1);
+            assert!(matches!(
+                pkcs1_pad(b"hello", &n),
+                Err(CryptopalsError::InvalidParameter(_))
+            ));
+        }
+
+        let n = BigUint::one() << (8 * 62 - 1);
+        let padded = pkcs1_pad(b"hello", &n).unwrap().to_bytes_be();
+        // the leading zero byte is dropped
+        assert_eq!(
+            [0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00],
+            padded[..10]
+        );
+    }
+}
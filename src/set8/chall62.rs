@@ -0,0 +1,207 @@
+use std::sync::OnceLock;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+    lattice, numtheory,
+};
+
+use super::{
+    chall59::{self, EcGroup, Point},
+    chall61,
+};
+
+pub struct Chall62;
+
+impl Challenge for Chall62 {
+    fn number(&self) -> u8 {
+        62
+    }
+
+    fn title(&self) -> &'static str {
+        "Key-Recovery Attacks on ECDSA with Biased Nonces"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall62()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches public key", true)
+    }
+}
+
+pub fn solve_chall62() -> error::Result<Output> {
+    let group = chall59::group();
+    let public_key = group.public_key(alice_private_key());
+
+    let signatures: Vec<_> = (0..SIGNATURES)
+        .map(|i| {
+            let message = format!("Message number {i}").into_bytes();
+            let signature = alice(&message);
+            (message, signature)
+        })
+        .collect();
+
+    let private_key = recover_private_key(group, &public_key, &signatures)?.ok_or(
+        CryptopalsError::AttackFailed("Failed to recover private key"),
+    )?;
+
+    Ok(Output::new()
+        .with("Alice's public key", format!("{public_key:?}"))
+        .with(
+            "Matches public key",
+            group.public_key(&private_key) == public_key,
+        )
+        .with("Recovered private key", private_key))
+}
+
+/// Number of low bits that are always zero in Alice's nonces.
+const BIASED_BITS: u64 = 8;
+
+/// Number of signatures to collect: each of them gives away `BIASED_BITS` bits of the private key.
+const SIGNATURES: usize = 22;
+
+static ALICE_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn alice_private_key() -> &'static BigUint {
+    ALICE_PRIVATE_KEY.get_or_init(|| chall59::group().random_private_key())
+}
+
+/// Alice signs anything she's given with ECDSA, but her nonces always have their low bits zeroed.
+fn alice(message: &[u8]) -> (BigUint, BigUint) {
+    let group = chall59::group();
+
+    loop {
+        let k = group.random_private_key() >> BIASED_BITS << BIASED_BITS;
+        if k.is_zero() {
+            continue;
+        }
+
+        if let Some(signature) =
+            chall61::ecdsa_sign_with_nonce(group, alice_private_key(), message, &k)
+        {
+            return signature;
+        }
+    }
+}
+
+fn to_signed(n: BigUint) -> BigInt {
+    BigInt::from_biguint(Sign::Plus, n)
+}
+
+/// Recovers the private key behind ECDSA signatures whose nonces have their `BIASED_BITS` low
+/// bits zeroed, as an instance of the Hidden Number Problem solved with LLL.
+///
+/// Returns `None` if the reduced basis doesn't give away the key (with too few signatures), and
+/// fails if any signature's `s` isn't invertible modulo `q` or if LLL can't reduce the basis.
+fn recover_private_key(
+    group: &EcGroup,
+    public_key: &Point,
+    signatures: &[(Vec<u8>, (BigUint, BigUint))],
+) -> error::Result<Option<BigUint>> {
+    let q = group.q();
+    let scale = BigUint::from(1u32) << BIASED_BITS;
+
+    // k = 2^l * b, so s = (h + d * r) / k gives d * t - u = b (mod q), with t = r / (s * 2^l),
+    // u = -h / (s * 2^l) and b < q / 2^l: d is the hidden number, and the b's are small
+    let (ts, us): (Vec<_>, Vec<_>) = signatures
+        .iter()
+        .map(|(message, (r, s))| {
+            let inverse = numtheory::modinv(&(s * &scale % q), q).ok_or(
+                CryptopalsError::InvalidParameter("Signature's s must be invertible modulo q"),
+            )?;
+            let h = chall61::hash_to_scalar(message, q);
+
+            Ok((r * &inverse % q, (q - h % q) * inverse % q))
+        })
+        .collect::<error::Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+
+    // rows q * e_i, then (t, 1 / 2^l, 0) and (u, 0, q / 2^l), all scaled up by 2^l to stay
+    // integral: d * (t row) - (u row) + (some multiples of the rest) is (b * 2^l, d, -q) which is
+    // much shorter than the rest of the lattice
+    let n = signatures.len();
+    let mut basis = vec![vec![BigInt::zero(); n + 2]; n + 2];
+    for i in 0..n {
+        basis[i][i] = to_signed(q * &scale);
+        basis[n][i] = to_signed(&ts[i] * &scale);
+        basis[n + 1][i] = to_signed(&us[i] * &scale);
+    }
+    basis[n][n] = 1.into();
+    basis[n + 1][n + 1] = to_signed(q.clone());
+
+    let q_signed = to_signed(q.clone());
+    Ok(lattice::lll(&basis, lattice::DEFAULT_DELTA)?
+        .into_iter()
+        .filter(|row| row[n + 1].abs() == q_signed)
+        .find_map(|row| {
+            // the row may be the vector we're after or its opposite
+            let d = if row[n + 1] == q_signed {
+                -&row[n]
+            } else {
+                row[n].clone()
+            };
+            let d = d.mod_floor(&q_signed).to_biguint()?;
+
+            (group.public_key(&d) == *public_key).then_some(d)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let group = chall59::group();
+        let public_key = group.public_key(alice_private_key());
+        let signatures: Vec<_> = (0..SIGNATURES)
+            .map(|i| {
+                let message = vec![i as u8];
+                let signature = alice(&message);
+                (message, signature)
+            })
+            .collect();
+
+        assert_eq!(
+            Some(alice_private_key()),
+            recover_private_key(group, &public_key, &signatures)
+                .unwrap()
+                .as_ref()
+        );
+    }
+
+    #[test]
+    fn rejects_non_invertible_s() {
+        let group = chall59::group();
+        let public_key = group.public_key(alice_private_key());
+        let mut signatures: Vec<_> = (0..2).map(|i| (vec![i], alice(&[i]))).collect();
+        signatures[1].1 .1 = group.q().clone();
+
+        assert!(matches!(
+            recover_private_key(group, &public_key, &signatures),
+            Err(CryptopalsError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn biased_signatures_verify() {
+        let group = chall59::group();
+        let public_key = group.public_key(alice_private_key());
+        let signature = alice(b"hello");
+
+        assert!(chall61::ecdsa_verify(
+            group,
+            &public_key,
+            b"hello",
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::OnceLock;
+
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error,
+    gf128::{Gf128, MulTable, Polynomial},
+    input::Input,
+    xor,
+};
+
+pub struct Chall63;
+
+impl Challenge for Chall63 {
+    fn number(&self) -> u8 {
+        63
+    }
+
+    fn title(&self) -> &'static str {
+        "Key-Recovery Attacks on GCM with Repeated Nonces"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall63()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Forged message accepted", "Pay Eve 999 dollars")
+    }
+}
+
+pub fn solve_chall63() -> error::Result<Output> {
+    let associated_data = "from: alice".as_bytes();
+    let messages: Vec<_> = [
+        "Pay Bob 100 dollars",
+        "Lunch at noon tomorrow?",
+        "Don't forget the milk",
+    ]
+    .iter()
+    .map(|plaintext| {
+        let (ciphertext, tag) = encrypt(associated_data, plaintext.as_bytes())?;
+        Ok((associated_data.to_vec(), ciphertext, tag))
+    })
+    .collect::<error::Result<_>>()?;
+
+    let candidates = recover_authentication_key(&messages);
+    let mut output = Output::new().with(
+        "Candidates for the authentication key",
+        format!("{candidates:x?}"),
+    );
+
+    // flip the first message into one of our own, and sign it with each candidate
+    let (_, ciphertext, _) = &messages[0];
+    let ciphertext = xor::xor(b"Pay Bob 100 dollars", b"Pay Eve 999 dollars")
+        .and_then(|difference| xor::xor(ciphertext, &difference))?;
+    for h in candidates {
+        let tag = forge_tag(h, &messages[0], associated_data, &ciphertext);
+        if let Some(plaintext) = decrypt(associated_data, &ciphertext, &tag)? {
+            output.push(
+                "Forged message accepted",
+                String::from_utf8_lossy(&plaintext).into_owned(),
+            );
+            break;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Encrypts a single block with AES-128.
+fn aes_block(key: &[u8], block: &[u8; 16]) -> error::Result<Gf128> {
+    Ok(Gf128::from_block(&block::encrypt_aes_ecb(block, key)?))
+}
+
+/// The counter block for a 96-bit nonce: the nonce, then a 32-bit big-endian counter.
+fn counter_block(nonce: &[u8; 12], counter: u32) -> [u8; 16] {
+    let mut block = [0; 16];
+    block[..12].copy_from_slice(nonce);
+    block[12..].copy_from_slice(&counter.to_be_bytes());
+
+    block
+}
+
+/// CTR mode as used by GCM, with the counter starting at 2 (1 is for the tag).
+fn ctr(key: &[u8], nonce: &[u8; 12], data: &[u8]) -> error::Result<Vec<u8>> {
+    let counters: Vec<u8> = (0..data.len().div_ceil(16))
+        .flat_map(|i| counter_block(nonce, i as u32 + 2))
+        .collect();
+    let keystream = block::encrypt_aes_ecb(&counters, key)?;
+
+    xor::xor(data, &keystream[..data.len()])
+}
+
+/// The blocks that GHASH goes through: the associated data and the ciphertext (each zero-padded to
+/// whole blocks), and then their lengths in bits.
+fn ghash_blocks(associated_data: &[u8], ciphertext: &[u8]) -> Vec<Gf128> {
+    let lengths = ((associated_data.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+
+    associated_data
+        .chunks(16)
+        .chain(ciphertext.chunks(16))
+        .map(Gf128::from_block)
+        .chain([Gf128(lengths)])
+        .collect()
+}
+
+/// GHASH: the blocks as coefficients of a polynomial evaluated at `h`, the first block having the
+/// highest degree.
+pub fn ghash(h: Gf128, associated_data: &[u8], ciphertext: &[u8]) -> Gf128 {
+    let table = MulTable::new(h);
+
+    ghash_blocks(associated_data, ciphertext)
+        .into_iter()
+        .fold(Gf128::ZERO, |acc, block| table.mul(acc + block))
+}
+
+/// AES-128-GCM encryption with a 96-bit nonce, returning the ciphertext and the tag.
+pub fn gcm_encrypt(
+    key: &[u8],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    plaintext: &[u8],
+) -> error::Result<(Vec<u8>, [u8; 16])> {
+    let ciphertext = ctr(key, nonce, plaintext)?;
+    let tag = gcm_tag(key, nonce, associated_data, &ciphertext)?;
+
+    Ok((ciphertext, tag))
+}
+
+/// AES-128-GCM decryption with a 96-bit nonce, returning `None` if the tag doesn't match.
+pub fn gcm_decrypt(
+    key: &[u8],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+) -> error::Result<Option<Vec<u8>>> {
+    if gcm_tag(key, nonce, associated_data, ciphertext)? != *tag {
+        return Ok(None);
+    }
+
+    ctr(key, nonce, ciphertext).map(Some)
+}
+
+/// The tag is the GHASH of everything, keyed by `H = E_K(0)`, and masked with `E_K(J_0)` (the
+/// counter block for 1).
+pub fn gcm_tag(
+    key: &[u8],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+) -> error::Result<[u8; 16]> {
+    let h = aes_block(key, &[0; 16])?;
+    let mask = aes_block(key, &counter_block(nonce, 1))?;
+
+    Ok((ghash(h, associated_data, ciphertext) + mask).to_block())
+}
+
+static KEY: OnceLock<[u8; 16]> = OnceLock::new();
+
+fn key() -> &'static [u8; 16] {
+    KEY.get_or_init(rand::random)
+}
+
+/// The server always encrypts with the same nonce.
+const NONCE: &[u8; 12] = b"not-a-nonce!";
+
+fn encrypt(associated_data: &[u8], plaintext: &[u8]) -> error::Result<(Vec<u8>, [u8; 16])> {
+    gcm_encrypt(key(), NONCE, associated_data, plaintext)
+}
+
+fn decrypt(
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+) -> error::Result<Option<Vec<u8>>> {
+    gcm_decrypt(key(), NONCE, associated_data, ciphertext, tag)
+}
+
+/// A message encrypted with GCM: its associated data, ciphertext and tag.
+type Message = (Vec<u8>, Vec<u8>, [u8; 16]);
+
+/// A message's tag as a polynomial in the authentication key: its GHASH blocks as coefficients,
+/// plus the tag as the constant term, so that it evaluates to the mask `E_K(J_0)` at `H`.
+fn tag_polynomial((associated_data, ciphertext, tag): &Message) -> Polynomial {
+    let mut coefficients = ghash_blocks(associated_data, ciphertext);
+    coefficients.push(Gf128::from_block(tag));
+    coefficients.reverse();
+
+    Polynomial::new(coefficients)
+}
+
+/// Recovers the candidates for the authentication key `H`, from messages encrypted with the same
+/// key and nonce: their tags are masked the same way, so `H` is a root of the sum of any two of
+/// their tag polynomials. Every other message rules out some of the wrong roots.
+fn recover_authentication_key(messages: &[Message]) -> Vec<Gf128> {
+    let polynomials: Vec<_> = messages.iter().map(tag_polynomial).collect();
+
+    (&polynomials[0] + &polynomials[1])
+        .roots()
+        .into_iter()
+        .filter(|&h| {
+            let mask = polynomials[0].evaluate(h);
+            polynomials[2..].iter().all(|p| p.evaluate(h) == mask)
+        })
+        .collect()
+}
+
+/// Forges the tag for a new message, given the authentication key and some message encrypted
+/// under the same nonce.
+fn forge_tag(h: Gf128, known: &Message, associated_data: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let mask = tag_polynomial(known).evaluate(h);
+
+    (ghash(h, associated_data, ciphertext) + mask).to_block()
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::symm::{self, Cipher};
+
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let messages: Vec<_> = ["attack at dawn", "attack at dusk", "retreat at once!!"]
+            .iter()
+            .map(|plaintext| {
+                let (ciphertext, tag) = encrypt(b"header", plaintext.as_bytes()).unwrap();
+                (b"header".to_vec(), ciphertext, tag)
+            })
+            .collect();
+
+        let candidates = recover_authentication_key(&messages);
+        assert_eq!(vec![aes_block(key(), &[0; 16]).unwrap()], candidates);
+
+        let difference = xor::xor(b"attack at dawn", b"defend at noon").unwrap();
+        let ciphertext = xor::xor(&messages[0].1, &difference).unwrap();
+        let tag = forge_tag(candidates[0], &messages[0], b"other header", &ciphertext);
+        assert_eq!(
+            Some(b"defend at noon".to_vec()),
+            decrypt(b"other header", &ciphertext, &tag).unwrap()
+        );
+    }
+
+    #[test]
+    fn gcm_matches_openssl() {
+        let key: [u8; 16] = rand::random();
+        let nonce: [u8; 12] = rand::random();
+
+        for (associated_data, plaintext) in [
+            (&b""[..], &b""[..]),
+            (b"", b"exactly 16 bytes"),
+            (
+                b"some associated data",
+                b"a plaintext that spans a few blocks",
+            ),
+        ] {
+            let mut expected_tag = [0; 16];
+            let expected = symm::encrypt_aead(
+                Cipher::aes_128_gcm(),
+                &key,
+                Some(&nonce),
+                associated_data,
+                plaintext,
+                &mut expected_tag,
+            )
+            .unwrap();
+
+            let (ciphertext, tag) = gcm_encrypt(&key, &nonce, associated_data, plaintext).unwrap();
+            assert_eq!(expected, ciphertext);
+            assert_eq!(expected_tag, tag);
+
+            assert_eq!(
+                Some(plaintext.to_vec()),
+                gcm_decrypt(&key, &nonce, associated_data, &ciphertext, &tag).unwrap()
+            );
+            let mut tampered = tag;
+            tampered[0] ^= 1;
+            assert_eq!(
+                None,
+                gcm_decrypt(&key, &nonce, associated_data, &ciphertext, &tampered).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use rand::Rng;
+
+use crate::{
+    block,
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    gf128::Gf128,
+    gf2::BitMatrix,
+    input::Input,
+};
+
+use super::chall63;
+
+const SLOW: bool = true;
+
+pub struct Chall64;
+
+impl Challenge for Chall64 {
+    fn number(&self) -> u8 {
+        64
+    }
+
+    fn title(&self) -> &'static str {
+        "Key-Recovery Attacks on GCM with a Truncated MAC"
+    }
+
+    fn slow(&self) -> bool {
+        SLOW
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall64()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Matches", true)
+            .with("Forged tag accepted", true)
+    }
+}
+
+pub fn solve_chall64() -> error::Result<Output> {
+    // 2^17 blocks, so that there are 17 blocks multiplied by powers of H of the form 2^i
+    let plaintext = vec![b'A'; 16 << 17];
+    let (ciphertext, tag) = encrypt(&plaintext, TAG_BYTES)?;
+
+    let oracle = verifier(TAG_BYTES);
+    let queries = Cell::new(0_usize);
+    let h = recover_authentication_key(&ciphertext, &tag, |ciphertext, tag| {
+        queries.set(queries.get() + 1);
+        oracle(ciphertext, tag)
+    })?;
+
+    let our_ciphertext = "Not even a real ciphertext".as_bytes();
+    let our_tag = forge_tag(h, (&ciphertext, &tag), our_ciphertext);
+
+    Ok(Output::new()
+        .with("Queries", queries.get())
+        .with("Recovered authentication key", h.to_block().as_slice())
+        .with("Matches", h == authentication_key()?)
+        .with("Forged tag accepted", oracle(our_ciphertext, &our_tag)?))
+}
+
+/// Tags are truncated to this many bytes.
+const TAG_BYTES: usize = 4;
+
+static KEY: OnceLock<[u8; 16]> = OnceLock::new();
+
+fn key() -> &'static [u8; 16] {
+    KEY.get_or_init(rand::random)
+}
+
+const NONCE: &[u8; 12] = b"just a nonce";
+
+pub fn authentication_key() -> error::Result<Gf128> {
+    Ok(Gf128::from_block(&block::encrypt_aes_ecb(&[0; 16], key())?))
+}
+
+/// Encrypts with GCM, truncating the tag to the given number of bytes.
+pub fn encrypt(plaintext: &[u8], tag_bytes: usize) -> error::Result<(Vec<u8>, Vec<u8>)> {
+    let (ciphertext, tag) = chall63::gcm_encrypt(key(), NONCE, &[], plaintext)?;
+
+    Ok((ciphertext, tag[..tag_bytes].to_vec()))
+}
+
+/// Bob's side: checks that a message's tag, truncated to the given number of bytes, is valid.
+pub fn verifier(tag_bytes: usize) -> impl Fn(&[u8], &[u8]) -> error::Result<bool> {
+    move |ciphertext, tag| {
+        let expected = chall63::gcm_tag(key(), NONCE, &[], ciphertext)?;
+
+        Ok(tag.len() == tag_bytes && expected[..tag_bytes] == *tag)
+    }
+}
+
+/// Ferguson's attack on GCM with truncated tags: recovers the authentication key `H` from one
+/// genuine message (made of whole blocks), by making the oracle check forgeries of it.
+fn recover_authentication_key<F>(ciphertext: &[u8], tag: &[u8], oracle: F) -> error::Result<Gf128>
+where
+    F: Fn(&[u8], &[u8]) -> error::Result<bool>,
+{
+    forge_until_key_recovered(ciphertext.to_vec(), Gf128::ZERO, tag, oracle)
+}
+
+/// The attack itself, on a ciphertext made of whole blocks whose forgeries all also add
+/// `length_difference` to the length block. Fails on anything but one or more whole blocks.
+///
+/// Flipping bits `e_i` in the blocks multiplied by `H^(2^i)` changes the tag by
+/// `sum(e_i * H^(2^i))`, which is linear in `H` since squaring is: that's `Ad * h` for the matrix
+/// `Ad = sum(M(e_i) * S^i)` (on bit vectors), plus `M(e_0)` for the length difference `e_0`.
+/// Picking the `e_i` so that the first rows of `Ad` are zero makes forgeries more likely to get
+/// through, and each one that does shows that the rest of the tag rows of `Ad` are orthogonal to
+/// `h`. Those equations then let the next forgeries zero even more rows, until `h` is all that's
+/// left.
+pub fn forge_until_key_recovered<F>(
+    mut ciphertext: Vec<u8>,
+    length_difference: Gf128,
+    tag: &[u8],
+    oracle: F,
+) -> error::Result<Gf128>
+where
+    F: Fn(&[u8], &[u8]) -> error::Result<bool>,
+{
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+        return Err(CryptopalsError::Unsupported(
+            "Only ciphertexts of one or more whole blocks are supported",
+        ));
+    }
+
+    let blocks = ciphertext.len() / 16;
+    let tag_bits = tag.len() * 8;
+
+    // GHASH multiplies the length block by H, and then the blocks before it by ever higher powers,
+    // so the one multiplied by H^(2^i) comes 2^i - 1 blocks before the end
+    let n = (blocks + 1).ilog2() as usize;
+    let offset = |i: usize| 16 * (blocks + 1 - (1 << i));
+
+    let mut squarings = vec![Gf128::squaring_matrix()];
+    for _ in 1..n {
+        squarings.push(&squarings[0] * squarings.last().expect("Not empty"));
+    }
+    let units: Vec<_> = (0..128)
+        .map(|j| Gf128::unit(j).multiplication_matrix())
+        .collect();
+    let length_matrix = length_difference.multiplication_matrix();
+
+    let flip = |ciphertext: &mut [u8], flips: &[bool]| {
+        for (i, e) in flips.chunks(128).enumerate() {
+            let block = &mut ciphertext[offset(i + 1)..offset(i + 1) + 16];
+            for (b, e) in block.iter_mut().zip(Gf128::from_bits(e).to_block()) {
+                *b ^= e;
+            }
+        }
+    };
+
+    // equations that h is known to satisfy, as rows
+    let mut known = BitMatrix::zero(0, 128);
+    let mut rng = rand::thread_rng();
+    loop {
+        // h is in the span of these, so Ad * h only depends on Ad * X
+        let basis = known.kernel();
+        if basis.len() == 1 {
+            return Ok(Gf128::from_bits(&basis[0]));
+        }
+        let x = BitMatrix::from_columns(128, &basis);
+        let square_xs: Vec<_> = squarings.iter().map(|square| square * &x).collect();
+        let length_x = &length_matrix * &x;
+
+        // zero as many rows of Ad * X as possible while leaving some flips to pick from (and at
+        // least one row of the tag to learn about), which may take fewer rows if the length
+        // difference can't be made up for
+        let max_rows = (tag_bits - 1).min((n * 128 - 1) / basis.len());
+        let (particular, candidates) = (0..=max_rows)
+            .rev()
+            .find_map(|rows| {
+                let mut dependencies = BitMatrix::zero(rows * basis.len(), n * 128);
+                for (i, square_x) in square_xs.iter().enumerate() {
+                    for (j, unit) in units.iter().enumerate() {
+                        // the rows of Ad * X that flipping bit j of e_i contributes to
+                        let contribution = &unit.top_rows(rows) * square_x;
+                        for r in 0..rows {
+                            for c in 0..basis.len() {
+                                if contribution.get(r, c) {
+                                    dependencies.set(r * basis.len() + c, i * 128 + j, true);
+                                }
+                            }
+                        }
+                    }
+                }
+                let target: Vec<_> = (0..rows).flat_map(|r| length_x.row(r)).collect();
+
+                Some((dependencies.solve(&target)?, dependencies.kernel()))
+            })
+            .expect("No rows is always solvable");
+
+        let flips = loop {
+            let mut flips = particular.clone();
+            for candidate in &candidates {
+                if rng.gen() {
+                    for (bit, &c) in flips.iter_mut().zip(candidate) {
+                        *bit ^= c;
+                    }
+                }
+            }
+            // without a length difference, flipping nothing just gives back the genuine message
+            if length_difference == Gf128::ZERO && !flips.contains(&true) {
+                continue;
+            }
+
+            flip(&mut ciphertext, &flips);
+            let accepted = oracle(&ciphertext, tag)?;
+            flip(&mut ciphertext, &flips);
+
+            if accepted {
+                break flips;
+            }
+        };
+
+        // the forgery got through, so the tag rows of Ad are all orthogonal to h
+        let mut ad = length_matrix.clone();
+        for (i, e) in flips.chunks(128).enumerate() {
+            let term = &Gf128::from_bits(e).multiplication_matrix() * &squarings[i];
+            ad = &ad + &term;
+        }
+        known.append_rows(&ad.top_rows(tag_bits));
+    }
+}
+
+/// Forges a truncated tag for any ciphertext, given the authentication key and some genuine
+/// message: tags are the GHASH plus the same mask, and truncating them commutes with adding.
+pub fn forge_tag(h: Gf128, (ciphertext, tag): (&[u8], &[u8]), our_ciphertext: &[u8]) -> Vec<u8> {
+    let difference = chall63::ghash(h, &[], ciphertext) + chall63::ghash(h, &[], our_ciphertext);
+
+    tag.iter()
+        .zip(difference.to_block())
+        .map(|(t, d)| t ^ d)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        // the same attack as solve_chall64, but with smaller parameters so that it runs quickly:
+        // 16-bit tags over 2^9 blocks take around 2^8 queries, where 32-bit tags over 2^17 blocks
+        // take around 2^16 (chall65's tests run those)
+        let (ciphertext, tag) = encrypt(&[0; 16 << 9], 2).unwrap();
+        let oracle = verifier(2);
+        let h = recover_authentication_key(&ciphertext, &tag, &oracle).unwrap();
+
+        assert_eq!(authentication_key().unwrap(), h);
+
+        let our_tag = forge_tag(h, (&ciphertext, &tag), b"forged");
+        assert!(oracle(b"forged", &our_tag).unwrap());
+    }
+
+    #[test]
+    fn rejects_partial_blocks() {
+        let (ciphertext, tag) = encrypt(&[0; 100], TAG_BYTES).unwrap();
+
+        for ciphertext in [&ciphertext[..], &[]] {
+            assert!(matches!(
+                recover_authentication_key(ciphertext, &tag, verifier(TAG_BYTES)),
+                Err(CryptopalsError::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn verifier_works() {
+        let (ciphertext, tag) = encrypt(b"hello", TAG_BYTES).unwrap();
+        let oracle = verifier(TAG_BYTES);
+
+        assert!(oracle(&ciphertext, &tag).unwrap());
+        assert!(!oracle(&ciphertext, &tag[..2]).unwrap());
+        assert!(!oracle(b"hellp", &tag).unwrap());
+    }
+}
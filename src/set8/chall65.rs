@@ -0,0 +1,174 @@
+use std::cell::Cell;
+
+use crate::{
+    challenge::{Challenge, Output},
+    error,
+    gf128::Gf128,
+    input::Input,
+};
+
+use super::chall64;
+
+const SLOW: bool = true;
+
+pub struct Chall65;
+
+impl Challenge for Chall65 {
+    fn number(&self) -> u8 {
+        65
+    }
+
+    fn title(&self) -> &'static str {
+        "Truncated-MAC GCM Revisited"
+    }
+
+    fn slow(&self) -> bool {
+        SLOW
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall65()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new()
+            .with("Matches", true)
+            .with("Forged tag accepted", true)
+    }
+}
+
+pub fn solve_chall65() -> error::Result<Output> {
+    // just short of 2^17 blocks, with the last one partial
+    let plaintext = vec![b'A'; (16 << 17) - 7];
+    let (ciphertext, tag) = chall64::encrypt(&plaintext, TAG_BYTES)?;
+
+    let oracle = chall64::verifier(TAG_BYTES);
+    let queries = Cell::new(0_usize);
+    let h = recover_authentication_key(&ciphertext, &tag, |ciphertext, tag| {
+        queries.set(queries.get() + 1);
+        oracle(ciphertext, tag)
+    })?;
+
+    let our_ciphertext = "Any length will do".as_bytes();
+    let our_tag = chall64::forge_tag(h, (&ciphertext, &tag), our_ciphertext);
+
+    Ok(Output::new()
+        .with("Queries", queries.get())
+        .with("Recovered authentication key", h.to_block().as_slice())
+        .with("Matches", h == chall64::authentication_key()?)
+        .with("Forged tag accepted", oracle(our_ciphertext, &our_tag)?))
+}
+
+/// Tags are truncated to this many bytes.
+const TAG_BYTES: usize = 4;
+
+/// Ferguson's attack again, on a genuine message of any length: GHASH zero-pads a partial last
+/// block, so extending the ciphertext with zeros up to a whole block only changes the length
+/// block, by a known difference. The forgeries then all carry that difference, but get the whole
+/// last block to flip, and the attack makes up for it when picking the flips.
+fn recover_authentication_key<F>(ciphertext: &[u8], tag: &[u8], oracle: F) -> error::Result<Gf128>
+where
+    F: Fn(&[u8], &[u8]) -> error::Result<bool>,
+{
+    let mut extended = ciphertext.to_vec();
+    extended.resize(ciphertext.len().next_multiple_of(16), 0);
+    let length_difference = Gf128((ciphertext.len() as u128 * 8) ^ (extended.len() as u128 * 8));
+
+    chall64::forge_until_key_recovered(extended, length_difference, tag, oracle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf128::MulTable;
+
+    #[test]
+    fn is_correct() {
+        // a smaller instance: 16-bit tags, and just short of 2^9 blocks
+        let (ciphertext, tag) = chall64::encrypt(&[0; (16 << 9) - 7], 2).unwrap();
+        let oracle = chall64::verifier(2);
+        let h = recover_authentication_key(&ciphertext, &tag, &oracle).unwrap();
+
+        assert_eq!(chall64::authentication_key().unwrap(), h);
+
+        let our_tag = chall64::forge_tag(h, (&ciphertext, &tag), b"forged");
+        assert!(oracle(b"forged", &our_tag).unwrap());
+    }
+
+    #[test]
+    fn recovers_key_from_32_bit_tags() {
+        let (ciphertext, tag) = chall64::encrypt(&[0; (16 << 17) - 7], TAG_BYTES).unwrap();
+        let oracle = incremental_verifier(&ciphertext, &tag);
+        let h = recover_authentication_key(&ciphertext, &tag, &oracle).unwrap();
+
+        assert_eq!(chall64::authentication_key().unwrap(), h);
+    }
+
+    /// Answers like [`chall64::verifier`], but for ciphertexts with as many blocks as the genuine
+    /// one only hashes the blocks that differ from it (and the length block), GHASH being linear
+    /// in them. Comparing is so much cheaper than hashing 2^17 blocks that the 2^16 or so queries
+    /// of the 32-bit attack take seconds instead of minutes.
+    fn incremental_verifier<'a>(
+        genuine: &'a [u8],
+        tag: &'a [u8],
+    ) -> impl Fn(&[u8], &[u8]) -> error::Result<bool> + 'a {
+        let table = MulTable::new(chall64::authentication_key().unwrap());
+        // block k (from 0) is multiplied by H^(blocks + 1 - k), the length block by H
+        let blocks = genuine.len().div_ceil(16);
+        let mut powers = vec![Gf128::ONE];
+        for _ in 0..=blocks {
+            powers.push(table.mul(*powers.last().unwrap()));
+        }
+        let verifier = chall64::verifier(tag.len());
+
+        move |ciphertext, candidate| {
+            if ciphertext.len().div_ceil(16) != blocks {
+                return verifier(ciphertext, candidate);
+            }
+
+            let lengths = (ciphertext.len() as u128 * 8) ^ (genuine.len() as u128 * 8);
+            let mut difference = Gf128(lengths) * powers[1];
+            for (chunk, (ours, theirs)) in ciphertext
+                .chunks(4096)
+                .zip(genuine.chunks(4096))
+                .enumerate()
+            {
+                if ours == theirs {
+                    continue;
+                }
+                for (i, (a, b)) in ours.chunks(16).zip(theirs.chunks(16)).enumerate() {
+                    // partial last blocks are zero-padded
+                    let delta = Gf128::from_block(a) + Gf128::from_block(b);
+                    if delta != Gf128::ZERO {
+                        difference = difference + delta * powers[blocks + 1 - (chunk * 256 + i)];
+                    }
+                }
+            }
+            let expected = tag.iter().zip(difference.to_block()).map(|(t, d)| t ^ d);
+
+            Ok(candidate.len() == tag.len() && candidate.iter().copied().eq(expected))
+        }
+    }
+
+    #[test]
+    fn incremental_verifier_agrees() {
+        let (ciphertext, tag) = chall64::encrypt(&[0; 100], TAG_BYTES).unwrap();
+        let oracle = incremental_verifier(&ciphertext, &tag);
+        let verifier = chall64::verifier(TAG_BYTES);
+
+        let mut forged = ciphertext.clone();
+        forged[3] ^= 1;
+        forged[99] ^= 0x80;
+        forged.extend([0, 7]);
+        let h = chall64::authentication_key().unwrap();
+        let forged_tag = chall64::forge_tag(h, (&ciphertext, &tag), &forged);
+
+        assert!(oracle(&ciphertext, &tag).unwrap());
+        assert!(!oracle(&forged, &tag).unwrap());
+        assert!(oracle(&forged, &forged_tag).unwrap());
+        assert!(verifier(&forged, &forged_tag).unwrap());
+        // other lengths go to the real verifier
+        let short_tag = chall64::forge_tag(h, (&ciphertext, &tag), b"short");
+        assert!(oracle(b"short", &short_tag).unwrap());
+    }
+}
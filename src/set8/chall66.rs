@@ -0,0 +1,310 @@
+use std::{cell::Cell, sync::OnceLock};
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::{
+    challenge::{Challenge, Output},
+    error::{self, CryptopalsError},
+    input::Input,
+};
+
+use super::chall57::{self, DhGroup};
+
+pub struct Chall66;
+
+impl Challenge for Chall66 {
+    fn number(&self) -> u8 {
+        66
+    }
+
+    fn title(&self) -> &'static str {
+        "Exploiting Implementation Errors in Diffie-Hellman"
+    }
+
+    fn solve(&self, _: &Input) -> error::Result<Output> {
+        solve_chall66()
+    }
+
+    fn expected(&self) -> Output {
+        Output::new().with("Matches public key", true)
+    }
+}
+
+pub fn solve_chall66() -> error::Result<Output> {
+    let group = chall57::group();
+    let public_key = group.public_key(bob_private_key());
+
+    let queries = Cell::new(0_usize);
+    let private_key = recover_private_key(group, &public_key, |public_key, message, mac| {
+        queries.set(queries.get() + 1);
+        bob(public_key, message, mac)
+    })?;
+
+    Ok(Output::new()
+        .with("Queries", queries.get())
+        .with(
+            "Matches public key",
+            group.public_key(&private_key) == public_key,
+        )
+        .with("Bob's public key", public_key)
+        .with("Recovered private key", private_key))
+}
+
+/// Number of low bits of a carry that must all be set for Bob's multiplication to go wrong.
+const FAULT_BITS: u32 = 10;
+
+const FAULT_MASK: u64 = (1 << FAULT_BITS) - 1;
+
+/// The carry out of the lowest 64-bit limb of `a * b`.
+fn lowest_carry(a: &BigUint, b: &BigUint) -> u64 {
+    let lowest = |n: &BigUint| n.iter_u64_digits().next().unwrap_or(0) as u128;
+
+    ((lowest(a) * lowest(b)) >> 64) as u64
+}
+
+/// Whether Bob's multiplication gets `a * b` wrong.
+fn faults(a: &BigUint, b: &BigUint) -> bool {
+    lowest_carry(a, b) & FAULT_MASK == FAULT_MASK
+}
+
+/// Bob's multiplication modulo `p`, with the kind of carry bug that keeps turning up in
+/// hand-written bignum code: it works on 64-bit limbs, and when the carry out of the lowest one
+/// ends in `FAULT_BITS` set bits, it forgets to add it into the next one. Random operands only
+/// hit that once in 2^`FAULT_BITS`, so the tests never did.
+fn faulty_mul(a: &BigUint, b: &BigUint, p: &BigUint) -> BigUint {
+    let product = a * b;
+    if faults(a, b) {
+        (product - (BigUint::from(lowest_carry(a, b)) << 64)) % p
+    } else {
+        product % p
+    }
+}
+
+/// One step of the Montgomery ladder, going from `(x^k, x^(k + 1))` to
+/// `(x^(2k + bit), x^(2k + bit + 1))`: it always multiplies the two together, and squares the one
+/// that `bit` picks.
+fn ladder_step<F>((r0, r1): (BigUint, BigUint), bit: bool, mul: F) -> (BigUint, BigUint)
+where
+    F: Fn(&BigUint, &BigUint) -> BigUint,
+{
+    let product = mul(&r0, &r1);
+    if bit {
+        (product, mul(&r1, &r1))
+    } else {
+        (mul(&r0, &r0), product)
+    }
+}
+
+/// Bob's modular exponentiation: a Montgomery ladder through every bit of an exponent below `q`
+/// (so that it always takes as long), on top of the faulty multiplication.
+fn faulty_pow(group: &DhGroup, base: &BigUint, exponent: &BigUint) -> BigUint {
+    let mul = |a: &BigUint, b: &BigUint| faulty_mul(a, b, group.p());
+
+    (0..group.q().bits())
+        .rev()
+        .fold((BigUint::one(), base.clone()), |state, i| {
+            ladder_step(state, exponent.bit(i), mul)
+        })
+        .0
+}
+
+static BOB_PRIVATE_KEY: OnceLock<BigUint> = OnceLock::new();
+
+fn bob_private_key() -> &'static BigUint {
+    BOB_PRIVATE_KEY.get_or_init(|| chall57::group().random_private_key())
+}
+
+/// Bob's side of the handshake: given someone's public key and a message with its MAC keyed by
+/// the shared secret, tells whether the MAC is valid.
+fn bob(public_key: &BigUint, message: &[u8], mac: &[u8]) -> error::Result<bool> {
+    let shared_secret = faulty_pow(chall57::group(), public_key, bob_private_key());
+
+    Ok(chall57::hmac_sha256(&shared_secret.to_bytes_be(), message)? == mac)
+}
+
+/// Number of public keys to keep track of at a time, of which about 2^-`FAULT_BITS` will be of
+/// use for each bit.
+const POOL_SIZE: usize = 1 << 12;
+
+/// A public key of ours, along with Bob's ladder state for it, as far as his key is known.
+struct Candidate {
+    private_key: BigUint,
+    public_key: BigUint,
+    state: (BigUint, BigUint),
+}
+
+/// Recovers the private key behind an oracle like [`bob`], from the top bit down.
+///
+/// With the bits above known, Bob's ladder state for any public key is known too, and the next bit
+/// decides which of its two values he squares, and so what he multiplies on the step after. For a
+/// public key that makes him fault on those for one value of the bit but not the other, a
+/// handshake that goes through rules out the first one (while one that fails could also just have
+/// faulted further down the ladder).
+///
+/// Until the top bit of his key, Bob's ladder only ever squares 1, so faults can't tell how many
+/// bits come before it: start from there instead, and stop once the bits give his public key
+/// (with or without more 0 bits after them).
+fn recover_private_key<F>(
+    group: &DhGroup,
+    bob_public_key: &BigUint,
+    oracle: F,
+) -> error::Result<BigUint>
+where
+    F: Fn(&BigUint, &[u8], &[u8]) -> error::Result<bool>,
+{
+    let mul = |a: &BigUint, b: &BigUint| a * b % group.p();
+    let message = "Hello, Bob".as_bytes();
+    let handshake_goes_through = |candidate: &Candidate| -> error::Result<bool> {
+        let shared_secret = bob_public_key.modpow(&candidate.private_key, group.p());
+        let mac = chall57::hmac_sha256(&shared_secret.to_bytes_be(), message)?;
+
+        oracle(&candidate.public_key, message, &mac)
+    };
+
+    // public keys for which Bob's ladder hasn't faulted yet, so far as his key is known
+    let new_candidate = |known: &BigUint| {
+        let private_key = group.random_private_key();
+        let public_key = group.public_key(&private_key);
+        let state = (0..known.bits())
+            .rev()
+            .try_fold((BigUint::one(), public_key.clone()), |state, i| {
+                advance(state, known.bit(i), mul)
+            })?;
+
+        Some(Candidate {
+            private_key,
+            public_key,
+            state,
+        })
+    };
+    // whether Bob faults on the next step or the multiplication after it, given the bit
+    let faults_for_sure = |state: &(BigUint, BigUint), bit: bool| {
+        advance(state.clone(), bit, mul).is_none_or(|(r0, r1)| faults(&r0, &r1))
+    };
+
+    // the key if it's `start` followed by nothing but 0 bits
+    let with_zeros = |start: BigUint| {
+        let mut public_key = group.public_key(&start);
+        let mut key = start;
+        while key.bits() <= group.q().bits() {
+            if public_key == *bob_public_key {
+                return Some(key);
+            }
+            public_key = mul(&public_key, &public_key);
+            key <<= 1;
+        }
+
+        None
+    };
+
+    let mut known = BigUint::one();
+    let mut pool = vec![];
+    loop {
+        // faults that only reach the second value of the ladder come to nothing if the bits after
+        // are all 0 (as there's no step after the last one), but then the public key gives that
+        // away
+        let next = |bit: bool| &known << 1 | BigUint::from(bit);
+        if let Some(key) = with_zeros(known.clone()).or_else(|| with_zeros(next(true))) {
+            return Ok(key);
+        }
+        if known.bits() >= group.q().bits() {
+            return Err(CryptopalsError::AttackFailed(
+                "Oracle does not behave as expected",
+            ));
+        }
+
+        let bit = 'search: loop {
+            while pool.len() < POOL_SIZE {
+                pool.extend(new_candidate(&known));
+            }
+
+            // public keys that fault for only one value of the bit (each only worth testing once,
+            // since Bob's answer won't change)
+            let (useful, rest): (Vec<_>, Vec<_>) = pool.into_iter().partition(|candidate| {
+                faults_for_sure(&candidate.state, false) != faults_for_sure(&candidate.state, true)
+            });
+            pool = rest;
+
+            for candidate in useful {
+                if handshake_goes_through(&candidate)? {
+                    break 'search faults_for_sure(&candidate.state, false);
+                }
+            }
+
+            // no luck with these: get more
+            pool.extend((0..POOL_SIZE).filter_map(|_| new_candidate(&known)));
+        };
+
+        known = next(bit);
+        pool = pool
+            .into_iter()
+            .filter_map(|candidate| {
+                Some(Candidate {
+                    state: advance(candidate.state, bit, mul)?,
+                    ..candidate
+                })
+            })
+            .collect();
+    }
+}
+
+/// Takes one step of Bob's ladder with correct arithmetic, or returns `None` if his would fault.
+fn advance<F>(state: (BigUint, BigUint), bit: bool, mul: F) -> Option<(BigUint, BigUint)>
+where
+    F: Fn(&BigUint, &BigUint) -> BigUint,
+{
+    let (r0, r1) = &state;
+    let squared = if bit { r1 } else { r0 };
+    if faults(r0, r1) || faults(squared, squared) {
+        return None;
+    }
+
+    Some(ladder_step(state, bit, mul))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+
+    use super::*;
+
+    #[test]
+    fn is_correct() {
+        let group = chall57::group();
+        let public_key = group.public_key(bob_private_key());
+
+        assert_eq!(
+            bob_private_key(),
+            &recover_private_key(group, &public_key, bob).unwrap()
+        );
+    }
+
+    #[test]
+    fn faulty_mul_works() {
+        let p = chall57::group().p();
+        let mut rng = rand::thread_rng();
+
+        let mut faulted = false;
+        while !faulted {
+            let (a, b) = (rng.gen_biguint_below(p), rng.gen_biguint_below(p));
+            faulted = faults(&a, &b);
+            assert_eq!(faulted, faulty_mul(&a, &b, p) != &a * &b % p);
+        }
+    }
+
+    #[test]
+    fn ladder_works() {
+        let group = chall57::group();
+        let (base, exponent) = (group.random_private_key(), group.random_private_key());
+        let mul = |a: &BigUint, b: &BigUint| a * b % group.p();
+
+        let (result, next) = (0..group.q().bits())
+            .rev()
+            .fold((BigUint::one(), base.clone()), |state, i| {
+                ladder_step(state, exponent.bit(i), mul)
+            });
+        assert_eq!(base.modpow(&exponent, group.p()), result);
+        assert_eq!(mul(&result, &base), next);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::{self, CryptopalsError};
+
+/// XORs two sequences of the same length.
+pub fn xor(a: &[u8], b: &[u8]) -> error::Result<Vec<u8>> {
+    if a.len() != b.len() {
+        return Err(CryptopalsError::LengthMismatch {
+            expected: a.len(),
+            actual: b.len(),
+        });
+    }
+
+    Ok(a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect())
+}
+
+/// XORs a single byte into every byte of a sequence.
+pub fn single_byte_xor(text: &[u8], key: u8) -> Vec<u8> {
+    text.iter().map(|x| x ^ key).collect()
+}
+
+/// XORs a sequence with a key repeated over its whole length.
+pub fn repeating_key_xor(plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+    plaintext
+        .iter()
+        .zip(key.iter().cycle())
+        .map(|(p, k)| p ^ k)
+        .collect()
+}
+
+/// Number of differing bits between two sequences of the same length.
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> error::Result<u32> {
+    if a.len() != b.len() {
+        return Err(CryptopalsError::LengthMismatch {
+            expected: a.len(),
+            actual: b.len(),
+        });
+    }
+
+    Ok(a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xor_rejects_different_lengths() {
+        assert!(matches!(
+            xor(b"abc", b"ab"),
+            Err(CryptopalsError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn hamming_distance_works() {
+        assert_eq!(
+            37,
+            hamming_distance("this is a test".as_bytes(), "wokka wokka!!!".as_bytes()).unwrap()
+        )
+    }
+
+    #[test]
+    fn hamming_distance_counts_past_255() {
+        assert_eq!(512, hamming_distance(&[0; 64], &[0xff; 64]).unwrap());
+    }
+}